@@ -1,5 +1,10 @@
+use std::fmt::Write as _;
+
 use rclrust_msg_types as types;
 
+use crate::parse::RosPackageMsgsMap;
+use crate::proto;
+
 /// Keywords in Rust
 /// https://doc.rust-lang.org/reference/keywords.html
 const RUST_KEYWORDS: [&str; 51] = [
@@ -17,8 +22,13 @@ const RUST_KEYWORDS: [&str; 51] = [
     "try",
 ];
 
+/// Keywords which cannot be raw identifiers
+const NON_RAW_KEYWORDS: [&str; 4] = ["crate", "self", "Self", "super"];
+
 pub fn escape_keyword(s: &str) -> String {
-    if RUST_KEYWORDS.contains(&s) {
+    if NON_RAW_KEYWORDS.contains(&s) {
+        format!("{}_", s)
+    } else if RUST_KEYWORDS.contains(&s) {
         format!("r#{}", s)
     } else {
         s.into()
@@ -27,9 +37,77 @@ pub fn escape_keyword(s: &str) -> String {
 
 pub const RCLRS_MSG_CORE: &str = "rclrust_msg_core";
 pub const MSG_TYPE_SUPPORT_PREFIX: &str = "rosidl_typesupport_c__get_message_type_support_handle";
+pub const MSG_INTROSPECTION_TYPE_SUPPORT_PREFIX: &str =
+    "rosidl_typesupport_introspection_c__get_message_type_support_handle";
 pub const SRV_TYPE_SUPPORT_PREFIX: &str = "rosidl_typesupport_c__get_service_type_support_handle";
 pub const ACTION_TYPE_SUPPORT_PREFIX: &str = "rosidl_typesupport_c__get_action_type_support_handle";
 
+/// How the generated packages are laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout<'a> {
+    /// All the packages are modules of the module at the given path, e.g. `crate::std_msgs` for
+    /// `crate`
+    Modules(&'a str),
+    /// Each package is a crate of its own, e.g. `::std_msgs`
+    Crates,
+}
+
+impl<'a> Layout<'a> {
+    /// Returns the path to the module containing the packages, or to the crate of the current one.
+    pub const fn root(self) -> &'a str {
+        match self {
+            Self::Modules(root) => root,
+            Self::Crates => "crate",
+        }
+    }
+
+    /// Returns the path to `package` from the code generated for `current`.
+    pub fn package_path(self, package: &str, current: &str) -> String {
+        match self {
+            Self::Modules(root) => format!("{}::{}", root, package),
+            Self::Crates if package == current => "crate".into(),
+            Self::Crates => format!("::{}", package),
+        }
+    }
+
+    /// Returns the path to `t` from the code generated for `current`.
+    pub fn namespaced_type_path(self, t: &types::NamespacedType, current: &str) -> String {
+        format!(
+            "{}::{}::{}",
+            self.package_path(&t.package, current),
+            t.namespace,
+            t.name
+        )
+    }
+}
+
+/// Returns whether `msg` is a plain message whose Rust structure can have the layout of its C
+/// structure, looking up the nested types in `packages`.
+///
+/// Messages without members are not plain, since their C structures have a dummy member.
+pub fn is_plain_message(msg: &types::Message, packages: &RosPackageMsgsMap) -> bool {
+    let resolve = |t: &types::NamespacedType| {
+        packages
+            .get(&t.package)
+            .and_then(|msgs| msgs.message(&t.namespace, &t.name))
+            .cloned()
+    };
+    !msg.members.is_empty()
+        && msg.class(&resolve) == types::MessageClass::Plain
+        && msg.members.iter().all(|member| {
+            let nested = match member.r#type.clone().inner_type() {
+                types::NestableType::NamedType(t) => types::NamespacedType {
+                    package: msg.package.clone(),
+                    namespace: "msg".into(),
+                    name: t.0,
+                },
+                types::NestableType::NamespacedType(t) => t,
+                _ => return true,
+            };
+            resolve(&nested).is_some_and(|nested| is_plain_message(&nested, packages))
+        })
+}
+
 pub fn create_zero_init_str(member: &types::Member) -> String {
     match member.r#type {
         types::MemberType::Array(ref t) => match t.value_type {
@@ -44,6 +122,58 @@ pub fn create_zero_init_str(member: &types::Member) -> String {
     }
 }
 
+fn basic_default(t: types::BasicType, literal: &str) -> String {
+    match t {
+        // Literals like `1` are valid defaults of floating point numbers
+        types::BasicType::F32 | types::BasicType::F64 => format!(
+            "{}{}_{}",
+            literal,
+            if literal.ends_with('.') { "0" } else { "" },
+            t.to_rust_str()
+        ),
+        _ => literal.into(),
+    }
+}
+
+/// Returns the elements of an array `literal` of basic values, or `literal` itself if it is not
+/// an array.
+fn basic_defaults(t: types::BasicType, literal: &str) -> impl Iterator<Item = String> + '_ {
+    literal
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(", ")
+        .filter(|v| !v.is_empty())
+        .map(move |v| basic_default(t, v))
+}
+
+/// Returns the expression of the default value of a member.
+pub fn create_default_str(member: &types::Member) -> String {
+    let default = match member.default {
+        Some(ref default) => default,
+        None => return create_zero_init_str(member),
+    };
+    let string_from = match member.r#type.clone().inner_type() {
+        types::NestableType::GenericString(t) if t.is_wide() => {
+            format!("{}::widestring::U16String::from_str", RCLRS_MSG_CORE)
+        }
+        types::NestableType::GenericString(_) => "std::string::String::from".into(),
+        types::NestableType::BasicType(t) => {
+            let elements = basic_defaults(t, default).collect::<Vec<_>>().join(", ");
+            return match member.r#type {
+                types::MemberType::BasicType(_) => elements,
+                types::MemberType::Array(_) => format!("[{}]", elements),
+                _ => format!("vec![{}]", elements),
+            };
+        }
+        _ => unreachable!("nested messages have no default values"),
+    };
+    match member.r#type {
+        types::MemberType::GenericString(_) => format!("{}({})", string_from, default),
+        types::MemberType::Array(_) => format!("{}.map({})", default, string_from),
+        _ => format!("std::vec::Vec::from({}.map({}))", default, string_from),
+    }
+}
+
 const ITER_TO_ARRAY: &str = ".collect::<Vec<_>>().try_into().unwrap()";
 
 pub fn create_ffi_to_rust(member: &types::Member) -> String {
@@ -52,10 +182,7 @@ pub fn create_ffi_to_rust(member: &types::Member) -> String {
         types::MemberType::BasicType(_) => format!("self.{}", name),
         types::MemberType::Array(ref t) => match t.value_type {
             types::NestableType::BasicType(_) => format!("self.{}.clone()", name),
-            _ => format!(
-                "self.{}.iter().map(_FFIToRust::to_rust){}",
-                name, ITER_TO_ARRAY
-            ),
+            _ => format!("self.{}.iter().map(|v| v.to_rust()){}", name, ITER_TO_ARRAY),
         },
         _ => format!("self.{}.to_rust()", name),
     }
@@ -76,21 +203,30 @@ pub fn create_ffi_from_rust(member: &types::Member) -> String {
     }
 }
 
-pub fn msg_type_to_ffi_raw(member: &types::Member, pkg_name: &str) -> String {
-    let rs_inner_type = match member.r#type.clone().inner_type() {
-        types::NestableType::BasicType(ref t) => t.to_rust_str().into(),
-        types::NestableType::NamedType(ref t) => format!("crate::{}::msg::{}_Raw", pkg_name, t.0),
-        types::NestableType::NamespacedType(ref t) => {
-            format!("crate::{}_Raw", t.to_rust_str())
+fn nestable_type_to_ffi_raw(t: &types::NestableType, pkg_name: &str, layout: Layout<'_>) -> String {
+    match t {
+        types::NestableType::BasicType(t) => t.to_rust_str().into(),
+        types::NestableType::NamedType(t) => format!(
+            "{}::msg::{}_Raw",
+            layout.package_path(pkg_name, pkg_name),
+            t.0
+        ),
+        types::NestableType::NamespacedType(t) => {
+            format!("{}_Raw", layout.namespaced_type_path(t, pkg_name))
         }
-        types::NestableType::GenericString(ref t) => {
+        types::NestableType::GenericString(t) => {
             if t.is_wide() {
                 format!("{}::FFIWString", RCLRS_MSG_CORE)
             } else {
                 format!("{}::FFIString", RCLRS_MSG_CORE)
             }
         }
-    };
+    }
+}
+
+pub fn msg_type_to_ffi_raw(member: &types::Member, pkg_name: &str, layout: Layout<'_>) -> String {
+    let rs_inner_type =
+        nestable_type_to_ffi_raw(&member.r#type.clone().inner_type(), pkg_name, layout);
 
     match member.r#type {
         types::MemberType::Array(ref t) => format!("[{}; {}]", rs_inner_type, t.size),
@@ -101,14 +237,20 @@ pub fn msg_type_to_ffi_raw(member: &types::Member, pkg_name: &str) -> String {
     }
 }
 
-pub fn msg_type_to_ffi_raw_ref(member: &types::Member, pkg_name: &str) -> String {
+pub fn msg_type_to_ffi_raw_ref(
+    member: &types::Member,
+    pkg_name: &str,
+    layout: Layout<'_>,
+) -> String {
     let rs_inner_type = match member.r#type.clone().inner_type() {
         types::NestableType::BasicType(ref t) => t.to_rust_str().into(),
-        types::NestableType::NamedType(ref t) => {
-            format!("crate::{}::msg::{}_RawRef", pkg_name, t.0)
-        }
+        types::NestableType::NamedType(ref t) => format!(
+            "{}::msg::{}_RawRef",
+            layout.package_path(pkg_name, pkg_name),
+            t.0
+        ),
         types::NestableType::NamespacedType(ref t) => {
-            format!("crate::{}_RawRef", t.to_rust_str())
+            format!("{}_RawRef", layout.namespaced_type_path(t, pkg_name))
         }
         types::NestableType::GenericString(ref t) => {
             if t.is_wide() {
@@ -134,21 +276,29 @@ pub fn msg_type_to_ffi_raw_ref(member: &types::Member, pkg_name: &str) -> String
     }
 }
 
-pub fn msg_type_to_rs_not_raw(member: &types::Member, pkg_name: &str) -> String {
-    let rs_inner_type = match member.r#type.clone().inner_type() {
-        types::NestableType::BasicType(ref t) => t.to_rust_str().into(),
-        types::NestableType::NamedType(ref t) => format!("crate::{}::msg::{}", pkg_name, t.0),
-        types::NestableType::NamespacedType(ref t) => {
-            format!("crate::{}", t.to_rust_str())
+fn nestable_type_to_rs(t: &types::NestableType, pkg_name: &str, layout: Layout<'_>) -> String {
+    match t {
+        types::NestableType::BasicType(t) => t.to_rust_str().into(),
+        types::NestableType::NamedType(t) => {
+            format!("{}::msg::{}", layout.package_path(pkg_name, pkg_name), t.0)
         }
-        types::NestableType::GenericString(ref t) => {
+        types::NestableType::NamespacedType(t) => layout.namespaced_type_path(t, pkg_name),
+        types::NestableType::GenericString(t) => {
             if t.is_wide() {
-                "crate::widestring::U16String".into()
+                format!("{}::widestring::U16String", RCLRS_MSG_CORE)
             } else {
                 "std::string::String".into()
             }
         }
-    };
+    }
+}
+
+pub fn msg_type_to_rs_not_raw(
+    member: &types::Member,
+    pkg_name: &str,
+    layout: Layout<'_>,
+) -> String {
+    let rs_inner_type = nestable_type_to_rs(&member.r#type.clone().inner_type(), pkg_name, layout);
 
     match member.r#type {
         types::MemberType::Array(ref t) => format!("[{}; {}]", rs_inner_type, t.size),
@@ -159,6 +309,325 @@ pub fn msg_type_to_rs_not_raw(member: &types::Member, pkg_name: &str) -> String
     }
 }
 
+fn nestable_type_to_value_type(
+    value_type: &types::NestableType,
+    pkg_name: &str,
+    layout: Layout<'_>,
+) -> String {
+    match value_type {
+        types::NestableType::BasicType(t) => format!("_ValueType::BasicType(_BasicType::{:?})", t),
+        types::NestableType::NamedType(t) => format!(
+            "_ValueType::Message(<{}::msg::{} as _MessageIntrospection>::DESCRIPTION)",
+            layout.package_path(pkg_name, pkg_name),
+            t.0
+        ),
+        types::NestableType::NamespacedType(t) => format!(
+            "_ValueType::Message(<{} as _MessageIntrospection>::DESCRIPTION)",
+            layout.namespaced_type_path(t, pkg_name)
+        ),
+        types::NestableType::GenericString(t) => {
+            format!("_ValueType::GenericString(_GenericString::{:?})", t)
+        }
+    }
+}
+
+pub fn create_field_type_description(
+    member: &types::Member,
+    pkg_name: &str,
+    layout: Layout<'_>,
+) -> String {
+    let value_type =
+        nestable_type_to_value_type(&member.r#type.clone().inner_type(), pkg_name, layout);
+    match member.r#type {
+        types::MemberType::Array(ref t) => format!(
+            "_FieldType::Array {{ value_type: {}, size: {} }}",
+            value_type, t.size
+        ),
+        types::MemberType::Sequence(_) => {
+            format!("_FieldType::Sequence {{ value_type: {} }}", value_type)
+        }
+        types::MemberType::BoundedSequence(ref t) => format!(
+            "_FieldType::BoundedSequence {{ value_type: {}, max_size: {} }}",
+            value_type, t.max_size
+        ),
+        _ => format!("_FieldType::Single({})", value_type),
+    }
+}
+
+/// Returns the layout of the C field of `member` as an expression of `_FieldLayout`.
+pub fn create_field_layout(member: &types::Member, pkg_name: &str, layout: Layout<'_>) -> String {
+    let inner = match member.r#type.clone().inner_type() {
+        types::NestableType::BasicType(t) => format!("_FieldLayout::basic(_BasicType::{:?})", t),
+        types::NestableType::NamedType(t) => format!(
+            "{}::msg::{}_Raw::LAYOUT.as_field()",
+            layout.package_path(pkg_name, pkg_name),
+            t.0
+        ),
+        types::NestableType::NamespacedType(t) => format!(
+            "{}_Raw::LAYOUT.as_field()",
+            layout.namespaced_type_path(&t, pkg_name)
+        ),
+        types::NestableType::GenericString(_) => "_FieldLayout::STRING".into(),
+    };
+    match member.r#type {
+        types::MemberType::Array(ref t) => format!("{}.array({})", inner, t.size),
+        types::MemberType::Sequence(_) | types::MemberType::BoundedSequence(_) => {
+            "_FieldLayout::SEQUENCE".into()
+        }
+        _ => inner,
+    }
+}
+
+/// Returns the entry of `member` of `<msg_name>_Raw` in the members table of the `mock-rosidl`
+/// introspection.
+pub fn create_mock_member(
+    member: &types::Member,
+    msg_name: &str,
+    pkg_name: &str,
+    layout: Layout<'_>,
+) -> String {
+    let value_type = member.r#type.clone().inner_type();
+    let type_id = match value_type {
+        types::NestableType::BasicType(t) => match t {
+            types::BasicType::I8 => "INT8",
+            types::BasicType::I16 => "INT16",
+            types::BasicType::I32 => "INT32",
+            types::BasicType::I64 => "INT64",
+            // `char` is `uint8` in IDL
+            types::BasicType::U8 | types::BasicType::Char => "UINT8",
+            types::BasicType::U16 => "UINT16",
+            types::BasicType::U32 => "UINT32",
+            types::BasicType::U64 => "UINT64",
+            types::BasicType::F32 => "FLOAT",
+            types::BasicType::F64 => "DOUBLE",
+            types::BasicType::Bool => "BOOLEAN",
+            types::BasicType::Byte => "OCTET",
+        },
+        types::NestableType::GenericString(ref t) if t.is_wide() => "WSTRING",
+        types::NestableType::GenericString(_) => "STRING",
+        types::NestableType::NamedType(_) | types::NestableType::NamespacedType(_) => "MESSAGE",
+    };
+    let mut entry = format!(
+        "_MessageMember::new(\"{}\\0\", _introspection_c::ROS_TYPE_{}, std::mem::offset_of!({}_Raw, {}))",
+        member.name,
+        type_id,
+        msg_name,
+        escape_keyword(&member.name)
+    );
+
+    let raw_type = nestable_type_to_ffi_raw(&value_type, pkg_name, layout);
+    match value_type {
+        types::NestableType::GenericString(
+            types::GenericString::BoundedString(bound)
+            | types::GenericString::BoundedWString(bound),
+        ) => write!(entry, ".string_upper_bound({})", bound).unwrap(),
+        types::NestableType::NamedType(_) | types::NestableType::NamespacedType(_) => write!(
+            entry,
+            ".nested_type_support(<{} as _MockMessage>::INTROSPECTION)",
+            raw_type
+        )
+        .unwrap(),
+        _ => {}
+    }
+    match member.r#type {
+        types::MemberType::Array(ref t) => {
+            write!(entry, ".array::<{}, {}>()", raw_type, t.size).unwrap()
+        }
+        types::MemberType::Sequence(_) => write!(entry, ".sequence::<{}>(0)", raw_type).unwrap(),
+        types::MemberType::BoundedSequence(ref t) => {
+            write!(entry, ".sequence::<{}>({})", raw_type, t.max_size).unwrap()
+        }
+        _ => {}
+    }
+    entry
+}
+
+pub fn create_default_description(member: &types::Member) -> String {
+    member
+        .default
+        .as_ref()
+        .map_or_else(|| "None".into(), |default| format!("Some({:?})", default))
+}
+
+fn prost_scalar_rust_type(t: types::BasicType) -> &'static str {
+    match proto::scalar_type(t) {
+        "int32" => "i32",
+        "uint32" => "u32",
+        _ => t.to_rust_str(),
+    }
+}
+
+/// Returns the `#[prost(...)]` attribute of a member with the field number `tag`.
+pub fn create_prost_attr(member: &types::Member, tag: usize) -> String {
+    let kind = match member.r#type.clone().inner_type() {
+        _ if proto::is_bytes(&member.r#type) => r#"bytes = "vec""#,
+        types::NestableType::BasicType(t) => proto::scalar_type(t),
+        types::NestableType::GenericString(_) => "string",
+        _ => "message",
+    };
+    let label = match member.r#type {
+        _ if proto::is_bytes(&member.r#type) => "",
+        types::MemberType::Array(_)
+        | types::MemberType::Sequence(_)
+        | types::MemberType::BoundedSequence(_) => ", repeated",
+        types::MemberType::NamedType(_) | types::MemberType::NamespacedType(_) => ", optional",
+        _ => "",
+    };
+    format!(r#"#[prost({}{}, tag = "{}")]"#, kind, label, tag)
+}
+
+/// Returns the type of a member in the representation deriving `prost::Message`.
+pub fn msg_type_to_prost(member: &types::Member, pkg_name: &str, layout: Layout<'_>) -> String {
+    if proto::is_bytes(&member.r#type) {
+        return "std::vec::Vec<u8>".into();
+    }
+    let rs_inner_type = match member.r#type.clone().inner_type() {
+        types::NestableType::BasicType(t) => prost_scalar_rust_type(t).into(),
+        types::NestableType::NamedType(ref t) => format!(
+            "{}::msg::{}_Proto",
+            layout.package_path(pkg_name, pkg_name),
+            t.0
+        ),
+        types::NestableType::NamespacedType(ref t) => {
+            format!("{}_Proto", layout.namespaced_type_path(t, pkg_name))
+        }
+        types::NestableType::GenericString(_) => "std::string::String".into(),
+    };
+
+    match member.r#type {
+        types::MemberType::Array(_)
+        | types::MemberType::Sequence(_)
+        | types::MemberType::BoundedSequence(_) => format!("std::vec::Vec<{}>", rs_inner_type),
+        types::MemberType::NamedType(_) | types::MemberType::NamespacedType(_) => {
+            format!("std::option::Option<{}>", rs_inner_type)
+        }
+        _ => rs_inner_type,
+    }
+}
+
+pub fn create_into_proto(member: &types::Member) -> String {
+    let name = escape_keyword(&member.name);
+    match member.r#type {
+        types::MemberType::Array(_) if proto::is_bytes(&member.r#type) => {
+            format!("self.{}.to_vec()", name)
+        }
+        _ if proto::is_bytes(&member.r#type) => format!("self.{}", name),
+        types::MemberType::NamedType(_) | types::MemberType::NamespacedType(_) => {
+            format!("Some(self.{}.into_proto())", name)
+        }
+        _ => format!("self.{}.into_proto()", name),
+    }
+}
+
+pub fn create_from_proto(member: &types::Member) -> String {
+    let name = escape_keyword(&member.name);
+    match member.r#type {
+        types::MemberType::Array(_) if proto::is_bytes(&member.r#type) => {
+            format!("_array_from_vec(proto.{})", name)
+        }
+        _ if proto::is_bytes(&member.r#type) => format!("proto.{}", name),
+        types::MemberType::NamedType(_) | types::MemberType::NamespacedType(_) => {
+            format!(
+                "_ProtoConvert::from_proto(proto.{}.unwrap_or_default())",
+                name
+            )
+        }
+        _ => format!("_ProtoConvert::from_proto(proto.{})", name),
+    }
+}
+
+/// Returns the bound of strings or sequences as an `Option` expression.
+fn bound_str(bound: Option<usize>) -> String {
+    bound.map_or_else(|| "None".into(), |bound| format!("Some({})", bound))
+}
+
+const fn string_bound(t: &types::GenericString) -> Option<usize> {
+    match t {
+        types::GenericString::BoundedString(bound)
+        | types::GenericString::BoundedWString(bound) => Some(*bound),
+        _ => None,
+    }
+}
+
+const fn sequence_bound(t: &types::MemberType) -> Option<usize> {
+    match t {
+        types::MemberType::BoundedSequence(t) => Some(t.max_size),
+        _ => None,
+    }
+}
+
+/// Returns the `proptest` strategy of the values of `member`.
+pub fn create_proptest_strategy(
+    member: &types::Member,
+    pkg_name: &str,
+    layout: Layout<'_>,
+) -> String {
+    let value_type = member.r#type.clone().inner_type();
+    let inner = match value_type {
+        types::NestableType::BasicType(types::BasicType::F32) => {
+            format!("{}::arbitrary::proptest::f32()", RCLRS_MSG_CORE)
+        }
+        types::NestableType::BasicType(types::BasicType::F64) => {
+            format!("{}::arbitrary::proptest::f64()", RCLRS_MSG_CORE)
+        }
+        types::NestableType::GenericString(ref t) => format!(
+            "{}::arbitrary::proptest::{}({})",
+            RCLRS_MSG_CORE,
+            if t.is_wide() { "wstring" } else { "string" },
+            bound_str(string_bound(t))
+        ),
+        _ => format!(
+            "proptest::arbitrary::any::<{}>()",
+            nestable_type_to_rs(&value_type, pkg_name, layout)
+        ),
+    };
+    match member.r#type {
+        types::MemberType::Array(ref t) => format!(
+            "{}::arbitrary::proptest::array::<_, {}>({})",
+            RCLRS_MSG_CORE, t.size, inner
+        ),
+        types::MemberType::Sequence(_) | types::MemberType::BoundedSequence(_) => format!(
+            "{}::arbitrary::proptest::sequence({}, {})",
+            RCLRS_MSG_CORE,
+            inner,
+            bound_str(sequence_bound(&member.r#type))
+        ),
+        _ => inner,
+    }
+}
+
+/// Returns the expression generating an arbitrary value of `member` from `g` with `quickcheck`.
+pub fn create_quickcheck_arbitrary(member: &types::Member) -> String {
+    let inner = match member.r#type.clone().inner_type() {
+        types::NestableType::BasicType(types::BasicType::F32) => {
+            format!("{}::arbitrary::quickcheck::f32(g)", RCLRS_MSG_CORE)
+        }
+        types::NestableType::BasicType(types::BasicType::F64) => {
+            format!("{}::arbitrary::quickcheck::f64(g)", RCLRS_MSG_CORE)
+        }
+        types::NestableType::GenericString(ref t) => format!(
+            "{}::arbitrary::quickcheck::{}(g, {})",
+            RCLRS_MSG_CORE,
+            if t.is_wide() { "wstring" } else { "string" },
+            bound_str(string_bound(t))
+        ),
+        _ => "quickcheck::Arbitrary::arbitrary(g)".into(),
+    };
+    match member.r#type {
+        types::MemberType::Array(_) => format!(
+            "{}::arbitrary::quickcheck::array(g, |g| {})",
+            RCLRS_MSG_CORE, inner
+        ),
+        types::MemberType::Sequence(_) | types::MemberType::BoundedSequence(_) => format!(
+            "{}::arbitrary::quickcheck::sequence(g, {}, |g| {})",
+            RCLRS_MSG_CORE,
+            bound_str(sequence_bound(&member.r#type)),
+            inner
+        ),
+        _ => inner,
+    }
+}
+
 pub fn constant_type_str(constant: &types::Constant) -> String {
     match constant.r#type {
         types::ConstantType::BasicType(ref t) => t.to_rust_str().into(),
@@ -174,6 +643,23 @@ pub fn constant_type_str(constant: &types::Constant) -> String {
     }
 }
 
+/// Returns the expression of the value of a constant.
+pub fn create_constant_value(constant: &types::Constant) -> String {
+    match constant.r#type {
+        types::ConstantType::BasicType(t) => basic_default(t, &constant.value),
+        types::ConstantType::PrimitiveArray(types::PrimitiveArray {
+            value_type: types::PrimitiveType::BasicType(t),
+            ..
+        }) => format!(
+            "[{}]",
+            basic_defaults(t, &constant.value)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        _ => constant.value.clone(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -182,5 +668,295 @@ mod tests {
     fn test_escape_keyword() {
         assert_eq!(escape_keyword("type"), "r#type");
         assert_eq!(escape_keyword("type2"), "type2");
+        assert_eq!(escape_keyword("self"), "self_");
+    }
+
+    #[test]
+    fn test_create_field_type_description() {
+        let member = types::Member {
+            name: "stamps".into(),
+            r#type: types::BoundedSequence {
+                value_type: types::NamespacedType {
+                    package: "builtin_interfaces".into(),
+                    namespace: "msg".into(),
+                    name: "Time".into(),
+                }
+                .into(),
+                max_size: 3,
+            }
+            .into(),
+            default: None,
+        };
+        assert_eq!(
+            create_field_type_description(&member, "std_msgs", Layout::Modules("crate")),
+            "_FieldType::BoundedSequence { value_type: _ValueType::Message(<crate::builtin_interfaces::msg::Time as _MessageIntrospection>::DESCRIPTION), max_size: 3 }"
+        );
+
+        let member = types::Member {
+            name: "name".into(),
+            r#type: types::GenericString::BoundedString(5).into(),
+            default: None,
+        };
+        assert_eq!(
+            create_field_type_description(&member, "std_msgs", Layout::Modules("crate")),
+            "_FieldType::Single(_ValueType::GenericString(_GenericString::BoundedString(5)))"
+        );
+    }
+
+    #[test]
+    fn test_create_field_layout() {
+        let member = types::Member {
+            name: "stamps".into(),
+            r#type: types::Array {
+                value_type: types::NamespacedType {
+                    package: "builtin_interfaces".into(),
+                    namespace: "msg".into(),
+                    name: "Time".into(),
+                }
+                .into(),
+                size: 2,
+            }
+            .into(),
+            default: None,
+        };
+        assert_eq!(
+            create_field_layout(&member, "std_msgs", Layout::Modules("crate")),
+            "crate::builtin_interfaces::msg::Time_Raw::LAYOUT.as_field().array(2)"
+        );
+
+        let member = types::Member {
+            name: "data".into(),
+            r#type: types::Sequence {
+                value_type: types::BasicType::F64.into(),
+            }
+            .into(),
+            default: None,
+        };
+        assert_eq!(
+            create_field_layout(&member, "std_msgs", Layout::Modules("crate")),
+            "_FieldLayout::SEQUENCE"
+        );
+
+        let member = types::Member {
+            name: "id".into(),
+            r#type: types::BasicType::U16.into(),
+            default: None,
+        };
+        assert_eq!(
+            create_field_layout(&member, "std_msgs", Layout::Modules("crate")),
+            "_FieldLayout::basic(_BasicType::U16)"
+        );
+    }
+
+    #[test]
+    fn test_create_mock_member() {
+        let member = types::Member {
+            name: "stamps".into(),
+            r#type: types::BoundedSequence {
+                value_type: types::NamespacedType {
+                    package: "builtin_interfaces".into(),
+                    namespace: "msg".into(),
+                    name: "Time".into(),
+                }
+                .into(),
+                max_size: 3,
+            }
+            .into(),
+            default: None,
+        };
+        assert_eq!(
+            create_mock_member(&member, "Sample", "std_msgs", Layout::Modules("crate")),
+            "_MessageMember::new(\"stamps\\0\", _introspection_c::ROS_TYPE_MESSAGE, std::mem::offset_of!(Sample_Raw, stamps))\
+             .nested_type_support(<crate::builtin_interfaces::msg::Time_Raw as _MockMessage>::INTROSPECTION)\
+             .sequence::<crate::builtin_interfaces::msg::Time_Raw>(3)"
+        );
+
+        let member = types::Member {
+            name: "names".into(),
+            r#type: types::Array {
+                value_type: types::GenericString::BoundedString(5).into(),
+                size: 2,
+            }
+            .into(),
+            default: None,
+        };
+        assert_eq!(
+            create_mock_member(&member, "Sample", "std_msgs", Layout::Modules("crate")),
+            "_MessageMember::new(\"names\\0\", _introspection_c::ROS_TYPE_STRING, std::mem::offset_of!(Sample_Raw, names))\
+             .string_upper_bound(5)\
+             .array::<rclrust_msg_core::FFIString, 2>()"
+        );
+    }
+
+    #[test]
+    fn test_create_default_str() {
+        let member = |r#type: types::MemberType, default: &str| types::Member {
+            name: "value".into(),
+            r#type,
+            default: Some(default.into()),
+        };
+        assert_eq!(
+            create_default_str(&member(types::BasicType::F32.into(), "1")),
+            "1_f32"
+        );
+        assert_eq!(
+            create_default_str(&member(types::BasicType::I8.into(), "-50")),
+            "-50"
+        );
+        assert_eq!(
+            create_default_str(&member(
+                types::Sequence {
+                    value_type: types::BasicType::F64.into()
+                }
+                .into(),
+                "[1.5, -2]"
+            )),
+            "vec![1.5_f64, -2_f64]"
+        );
+        assert_eq!(
+            create_default_str(&member(
+                types::GenericString::WString.into(),
+                r##"r#"Hello"#"##
+            )),
+            r##"rclrust_msg_core::widestring::U16String::from_str(r#"Hello"#)"##
+        );
+        assert_eq!(
+            create_default_str(&member(
+                types::Array {
+                    value_type: types::GenericString::String.into(),
+                    size: 2,
+                }
+                .into(),
+                r##"[r#""#, r#"max value"#]"##
+            )),
+            r##"[r#""#, r#"max value"#].map(std::string::String::from)"##
+        );
+    }
+
+    #[test]
+    fn test_arbitrary() {
+        let member = types::Member {
+            name: "names".into(),
+            r#type: types::BoundedSequence {
+                value_type: types::GenericString::BoundedWString(4).into(),
+                max_size: 3,
+            }
+            .into(),
+            default: None,
+        };
+        assert_eq!(
+            create_proptest_strategy(&member, "std_msgs", Layout::Modules("crate")),
+            "rclrust_msg_core::arbitrary::proptest::sequence(\
+             rclrust_msg_core::arbitrary::proptest::wstring(Some(4)), Some(3))"
+        );
+        assert_eq!(
+            create_quickcheck_arbitrary(&member),
+            "rclrust_msg_core::arbitrary::quickcheck::sequence(g, Some(3), \
+             |g| rclrust_msg_core::arbitrary::quickcheck::wstring(g, Some(4)))"
+        );
+
+        let member = types::Member {
+            name: "stamps".into(),
+            r#type: types::Array {
+                value_type: types::NamespacedType {
+                    package: "builtin_interfaces".into(),
+                    namespace: "msg".into(),
+                    name: "Time".into(),
+                }
+                .into(),
+                size: 2,
+            }
+            .into(),
+            default: None,
+        };
+        assert_eq!(
+            create_proptest_strategy(&member, "std_msgs", Layout::Crates),
+            "rclrust_msg_core::arbitrary::proptest::array::<_, 2>(\
+             proptest::arbitrary::any::<::builtin_interfaces::msg::Time>())"
+        );
+        assert_eq!(
+            create_quickcheck_arbitrary(&member),
+            "rclrust_msg_core::arbitrary::quickcheck::array(g, |g| quickcheck::Arbitrary::arbitrary(g))"
+        );
+    }
+
+    #[test]
+    fn test_layout() {
+        let member = types::Member {
+            name: "header".into(),
+            r#type: types::NamespacedType {
+                package: "std_msgs".into(),
+                namespace: "msg".into(),
+                name: "Header".into(),
+            }
+            .into(),
+            default: None,
+        };
+        assert_eq!(
+            msg_type_to_rs_not_raw(&member, "geometry_msgs", Layout::Modules("crate")),
+            "crate::std_msgs::msg::Header"
+        );
+        assert_eq!(
+            msg_type_to_rs_not_raw(&member, "geometry_msgs", Layout::Crates),
+            "::std_msgs::msg::Header"
+        );
+        assert_eq!(
+            msg_type_to_ffi_raw(&member, "std_msgs", Layout::Crates),
+            "crate::msg::Header_Raw"
+        );
+    }
+
+    #[test]
+    fn test_prost() {
+        let member = types::Member {
+            name: "data".into(),
+            r#type: types::Array {
+                value_type: types::BasicType::Byte.into(),
+                size: 4,
+            }
+            .into(),
+            default: None,
+        };
+        assert_eq!(
+            create_prost_attr(&member, 1),
+            r#"#[prost(bytes = "vec", tag = "1")]"#
+        );
+        assert_eq!(create_from_proto(&member), "_array_from_vec(proto.data)");
+
+        let member = types::Member {
+            name: "stamps".into(),
+            r#type: types::Sequence {
+                value_type: types::NamespacedType {
+                    package: "builtin_interfaces".into(),
+                    namespace: "msg".into(),
+                    name: "Time".into(),
+                }
+                .into(),
+            }
+            .into(),
+            default: None,
+        };
+        assert_eq!(
+            create_prost_attr(&member, 2),
+            r#"#[prost(message, repeated, tag = "2")]"#
+        );
+        assert_eq!(
+            msg_type_to_prost(&member, "std_msgs", Layout::Crates),
+            "std::vec::Vec<::builtin_interfaces::msg::Time_Proto>"
+        );
+
+        let member = types::Member {
+            name: "level".into(),
+            r#type: types::BasicType::I8.into(),
+            default: None,
+        };
+        assert_eq!(
+            create_prost_attr(&member, 3),
+            r#"#[prost(int32, tag = "3")]"#
+        );
+        assert_eq!(
+            msg_type_to_prost(&member, "std_msgs", Layout::Modules("crate")),
+            "i32"
+        );
     }
 }
@@ -0,0 +1,409 @@
+//! Export of the interfaces as OMG IDL in the way of `rosidl_adapter`
+//!
+//! Each message, service and action becomes `<package>/<namespace>/<Name>.idl` with the structures
+//! in the modules `<package>::<namespace>`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+
+use rclrust_msg_types::{
+    literal, BasicType, ConstantType, GenericString, GenericUnboundedString, Member, MemberType,
+    Message, NestableType,
+};
+
+use crate::parse::{to_msg_literal, RosPackageMsgs};
+
+/// Returns the path of the IDL file of an interface, e.g. `std_msgs/msg/Header.idl`.
+pub fn idl_path(package: &str, namespace: &str, name: &str) -> String {
+    format!("{}/{}/{}.idl", package, namespace, name)
+}
+
+const fn basic_type(t: BasicType) -> &'static str {
+    match t {
+        BasicType::I8 => "int8",
+        BasicType::I16 => "int16",
+        BasicType::I32 => "int32",
+        BasicType::I64 => "int64",
+        BasicType::U8 | BasicType::Char => "uint8",
+        BasicType::U16 => "uint16",
+        BasicType::U32 => "uint32",
+        BasicType::U64 => "uint64",
+        BasicType::F32 => "float",
+        BasicType::F64 => "double",
+        BasicType::Bool => "boolean",
+        BasicType::Byte => "octet",
+    }
+}
+
+fn nestable_type(t: &NestableType, package: &str) -> String {
+    match t {
+        NestableType::BasicType(t) => basic_type(*t).into(),
+        NestableType::NamedType(t) => format!("{}::msg::{}", package, t.0),
+        NestableType::NamespacedType(t) => format!("{}::{}::{}", t.package, t.namespace, t.name),
+        NestableType::GenericString(t) => match t {
+            GenericString::String => "string".into(),
+            GenericString::WString => "wstring".into(),
+            GenericString::BoundedString(max) => format!("string<{}>", max),
+            GenericString::BoundedWString(max) => format!("wstring<{}>", max),
+        },
+    }
+}
+
+/// Returns the `typedef` of an array member and its name, e.g. `double__9`.
+fn array_typedef(t: &MemberType, package: &str) -> Option<(String, String)> {
+    let array = match t {
+        MemberType::Array(array) => array,
+        _ => return None,
+    };
+    let base = nestable_type(&array.value_type, package);
+    let name = format!(
+        "{}__{}",
+        base.replace("::", "__").replace(['<', '>'], ""),
+        array.size
+    );
+    let typedef = format!("typedef {} {}[{}];", base, name, array.size);
+    Some((typedef, name))
+}
+
+fn member_type(t: &MemberType, package: &str) -> String {
+    if let Some((_, name)) = array_typedef(t, package) {
+        return name;
+    }
+    let inner = nestable_type(&t.clone().inner_type(), package);
+    match t {
+        MemberType::Sequence(_) => format!("sequence<{}>", inner),
+        MemberType::BoundedSequence(t) => format!("sequence<{}, {}>", inner, t.max_size),
+        _ => inner,
+    }
+}
+
+/// Converts a value parsed by `rclrust-msg-parser` into an IDL literal.
+fn idl_literal(value: &str) -> String {
+    match value {
+        "true" => "TRUE".into(),
+        "false" => "FALSE".into(),
+        _ => to_msg_literal(value),
+    }
+}
+
+/// Converts an element of a default array into the Python literal `rosidl_adapter` writes.
+fn python_literal(value: &str) -> String {
+    match (value, literal::parse_string(value)) {
+        ("true", _) => "True".into(),
+        ("false", _) => "False".into(),
+        (_, Some(s)) => {
+            let quote = if s.contains('\'') && !s.contains('"') {
+                '"'
+            } else {
+                '\''
+            };
+            let mut out = quote.to_string();
+            for c in s.chars() {
+                match c {
+                    '\\' => out.push_str("\\\\"),
+                    '\n' => out.push_str("\\n"),
+                    '\r' => out.push_str("\\r"),
+                    '\t' => out.push_str("\\t"),
+                    c if c == quote => {
+                        out.push('\\');
+                        out.push(c);
+                    }
+                    c => out.push(c),
+                }
+            }
+            out.push(quote);
+            out
+        }
+        (_, None) => value.into(),
+    }
+}
+
+fn default_annotation(member: &Member) -> Option<String> {
+    let default = member.default.as_deref()?;
+    let value = match member.r#type {
+        // `rosidl_adapter` writes arrays as Python tuples in strings, e.g. `"(1, 2)"`
+        MemberType::Array(_) | MemberType::Sequence(_) | MemberType::BoundedSequence(_) => {
+            let values = literal::parse_sequence(default)?
+                .into_iter()
+                .map(python_literal)
+                .collect::<Vec<_>>();
+            let tuple = match values.as_slice() {
+                [value] => format!("({},)", value),
+                _ => format!("({})", values.join(", ")),
+            };
+            format!("{:?}", tuple)
+        }
+        _ => idl_literal(default),
+    };
+    Some(format!("@default (value={})", value))
+}
+
+const fn constant_type(t: &ConstantType) -> Option<&'static str> {
+    Some(match t {
+        ConstantType::BasicType(t) => basic_type(*t),
+        ConstantType::GenericUnboundedString(GenericUnboundedString::String) => "string",
+        ConstantType::GenericUnboundedString(GenericUnboundedString::WString) => "wstring",
+        ConstantType::PrimitiveArray(_) => return None,
+    })
+}
+
+/// The contents of an IDL file of an interface
+struct Idl<'a> {
+    package: &'a str,
+    namespace: &'a str,
+    includes: BTreeSet<String>,
+    body: String,
+}
+
+impl<'a> Idl<'a> {
+    const fn new(package: &'a str, namespace: &'a str) -> Self {
+        Self {
+            package,
+            namespace,
+            includes: BTreeSet::new(),
+            body: String::new(),
+        }
+    }
+
+    fn message(&mut self, msg: &Message) {
+        let out = &mut self.body;
+        if !msg.constants.is_empty() {
+            writeln!(out, "    module {}_Constants {{", msg.name).unwrap();
+            for constant in &msg.constants {
+                match constant_type(&constant.r#type) {
+                    Some(t) => writeln!(
+                        out,
+                        "      const {} {} = {};",
+                        t,
+                        constant.name,
+                        idl_literal(&constant.value)
+                    ),
+                    None => writeln!(
+                        out,
+                        "      // {} {}={} is not representable in IDL",
+                        constant.r#type,
+                        constant.name,
+                        to_msg_literal(&constant.value)
+                    ),
+                }
+                .unwrap();
+            }
+            out.push_str("    };\n");
+        }
+
+        let typedefs = msg
+            .members
+            .iter()
+            .filter_map(|member| array_typedef(&member.r#type, &msg.package))
+            .map(|(typedef, _)| typedef)
+            .collect::<BTreeSet<_>>();
+        for typedef in typedefs {
+            writeln!(out, "    {}", typedef).unwrap();
+        }
+
+        writeln!(out, "    struct {} {{", msg.name).unwrap();
+        if msg.members.is_empty() {
+            out.push_str("      uint8 structure_needs_at_least_one_member;\n");
+        }
+        for (i, member) in msg.members.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            if let Some(annotation) = default_annotation(member) {
+                writeln!(out, "      {}", annotation).unwrap();
+            }
+            writeln!(
+                out,
+                "      {} {};",
+                member_type(&member.r#type, &msg.package),
+                member.name
+            )
+            .unwrap();
+
+            let include = match member.r#type.clone().inner_type() {
+                NestableType::NamedType(t) => idl_path(&msg.package, "msg", &t.0),
+                NestableType::NamespacedType(t) => idl_path(&t.package, &t.namespace, &t.name),
+                _ => continue,
+            };
+            self.includes.insert(include);
+        }
+        out.push_str("    };\n");
+    }
+
+    fn render(self, source: &str) -> String {
+        let mut out = format!(
+            "// generated by rclrust-msg\n\
+             // with input from {}\n",
+            source
+        );
+        if !self.includes.is_empty() {
+            out.push('\n');
+            for include in &self.includes {
+                writeln!(out, "#include \"{}\"", include).unwrap();
+            }
+        }
+        write!(
+            out,
+            "\nmodule {} {{\n  module {} {{\n{}  }};\n}};\n",
+            self.package, self.namespace, self.body
+        )
+        .unwrap();
+        out
+    }
+}
+
+/// Renders the interfaces of `package` as IDL files keyed by their [`idl_path`].
+pub fn render_idl(package: &str, msgs: &RosPackageMsgs) -> BTreeMap<String, String> {
+    let mut files = BTreeMap::new();
+    let mut render = |namespace, name: &str, messages: &[&Message]| {
+        let mut idl = Idl::new(package, namespace);
+        for msg in messages {
+            idl.message(msg);
+        }
+        let path = idl_path(package, namespace, name);
+        let source = format!("{}/{}/{}.{}", package, namespace, name, namespace);
+        files.insert(path, idl.render(&source));
+    };
+
+    for msg in &msgs.msgs {
+        render("msg", &msg.name, &[msg]);
+    }
+    for srv in &msgs.srvs {
+        render("srv", &srv.name, &[&srv.request, &srv.response]);
+    }
+    for action in &msgs.actions {
+        render(
+            "action",
+            &action.name,
+            &[&action.goal, &action.result, &action.feedback],
+        );
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use rclrust_msg_types::{Array, BoundedSequence, Constant, NamespacedType};
+
+    use super::*;
+
+    #[test]
+    fn test_member_type() {
+        let time = NamespacedType {
+            package: "builtin_interfaces".into(),
+            namespace: "msg".into(),
+            name: "Time".into(),
+        };
+        assert_eq!(
+            member_type(
+                &Array {
+                    value_type: time.clone().into(),
+                    size: 3,
+                }
+                .into(),
+                "pkg"
+            ),
+            "builtin_interfaces__msg__Time__3"
+        );
+        assert_eq!(
+            array_typedef(
+                &Array {
+                    value_type: GenericString::BoundedString(5).into(),
+                    size: 2,
+                }
+                .into(),
+                "pkg"
+            ),
+            Some((
+                "typedef string<5> string5__2[2];".into(),
+                "string5__2".into()
+            ))
+        );
+        assert_eq!(
+            member_type(
+                &BoundedSequence {
+                    value_type: time.into(),
+                    max_size: 4,
+                }
+                .into(),
+                "pkg"
+            ),
+            "sequence<builtin_interfaces::msg::Time, 4>"
+        );
+        assert_eq!(member_type(&BasicType::Byte.into(), "pkg"), "octet");
+    }
+
+    #[test]
+    fn test_default_annotation() {
+        let member = |r#type: MemberType, default: &str| Member {
+            name: "value".into(),
+            r#type,
+            default: Some(default.into()),
+        };
+        let strings = BoundedSequence {
+            value_type: GenericString::String.into(),
+            max_size: 3,
+        };
+        assert_eq!(
+            default_annotation(&member(
+                strings.clone().into(),
+                r##"[r#"a"#, r#"b 'c'"#, r#"d\"#]"##
+            ))
+            .unwrap(),
+            r#"@default (value="('a', \"b 'c'\", 'd\\\\')")"#
+        );
+        assert_eq!(
+            default_annotation(&member(strings.into(), r##"[r#"a"#]"##)).unwrap(),
+            r#"@default (value="('a',)")"#
+        );
+        assert_eq!(
+            default_annotation(&member(BasicType::I32.into(), "-1")).unwrap(),
+            "@default (value=-1)"
+        );
+    }
+
+    #[test]
+    fn test_render_idl() {
+        let msgs = RosPackageMsgs {
+            msgs: vec![Message {
+                package: "pkg".into(),
+                name: "Flags".into(),
+                members: vec![Member {
+                    name: "values".into(),
+                    r#type: Array {
+                        value_type: BasicType::Bool.into(),
+                        size: 2,
+                    }
+                    .into(),
+                    default: Some("[true, false]".into()),
+                }],
+                constants: vec![Constant {
+                    name: "ENABLED".into(),
+                    r#type: BasicType::Bool.into(),
+                    value: "true".into(),
+                }],
+            }],
+            srvs: vec![],
+            actions: vec![],
+        };
+        assert_eq!(
+            render_idl("pkg", &msgs)["pkg/msg/Flags.idl"],
+            "// generated by rclrust-msg
+// with input from pkg/msg/Flags.msg
+
+module pkg {
+  module msg {
+    module Flags_Constants {
+      const boolean ENABLED = TRUE;
+    };
+    typedef boolean boolean__2[2];
+    struct Flags {
+      @default (value=\"(True, False)\")
+      boolean__2 values;
+    };
+  };
+};
+"
+        );
+    }
+}
@@ -0,0 +1,243 @@
+//! Export of the interfaces as JSON Schema documents
+//!
+//! Each message, service request/response and action goal/result/feedback becomes
+//! `<package>/<namespace>/<Name>.json`. Nested types are referenced with relative `$ref`s.
+//! Fields are not required since missing fields take their default values, e.g. in rosbridge.
+
+use std::collections::BTreeMap;
+
+use rclrust_msg_types::literal;
+use rclrust_msg_types::{BasicType, GenericString, Member, MemberType, Message, NestableType};
+use serde_json::{json, Map, Number, Value};
+
+use crate::parse::RosPackageMsgs;
+
+const SCHEMA: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Returns the path of the schema of a type, e.g. `std_msgs/msg/Header.json`.
+pub fn schema_path(package: &str, namespace: &str, name: &str) -> String {
+    format!("{}/{}/{}.json", package, namespace, name)
+}
+
+fn basic_type_schema(t: BasicType) -> Value {
+    let (min, max): (Value, Value) = match t {
+        BasicType::I8 => (i8::MIN.into(), i8::MAX.into()),
+        BasicType::I16 => (i16::MIN.into(), i16::MAX.into()),
+        BasicType::I32 => (i32::MIN.into(), i32::MAX.into()),
+        BasicType::I64 => (i64::MIN.into(), i64::MAX.into()),
+        BasicType::U8 | BasicType::Char | BasicType::Byte => (0.into(), u8::MAX.into()),
+        BasicType::U16 => (0.into(), u16::MAX.into()),
+        BasicType::U32 => (0.into(), u32::MAX.into()),
+        BasicType::U64 => (0.into(), u64::MAX.into()),
+        BasicType::F32 | BasicType::F64 => return json!({ "type": "number" }),
+        BasicType::Bool => return json!({ "type": "boolean" }),
+    };
+    json!({ "type": "integer", "minimum": min, "maximum": max })
+}
+
+fn nestable_type_schema(t: &NestableType, package: &str, namespace: &str) -> Value {
+    match t {
+        NestableType::BasicType(t) => basic_type_schema(*t),
+        NestableType::NamedType(t) => {
+            let path = if namespace == "msg" {
+                format!("{}.json", t.0)
+            } else {
+                format!("../msg/{}.json", t.0)
+            };
+            json!({ "$ref": path })
+        }
+        NestableType::NamespacedType(t) => {
+            let path = if t.package == package && t.namespace == namespace {
+                format!("{}.json", t.name)
+            } else {
+                format!("../../{}", schema_path(&t.package, &t.namespace, &t.name))
+            };
+            json!({ "$ref": path })
+        }
+        NestableType::GenericString(t) => match t {
+            GenericString::BoundedString(max) | GenericString::BoundedWString(max) => {
+                json!({ "type": "string", "maxLength": max })
+            }
+            _ => json!({ "type": "string" }),
+        },
+    }
+}
+
+fn member_type_schema(t: &MemberType, package: &str, namespace: &str) -> Value {
+    let items = nestable_type_schema(&t.clone().inner_type(), package, namespace);
+    match t {
+        MemberType::Array(t) => json!({
+            "type": "array",
+            "items": items,
+            "minItems": t.size,
+            "maxItems": t.size,
+        }),
+        MemberType::Sequence(_) => json!({ "type": "array", "items": items }),
+        MemberType::BoundedSequence(t) => json!({
+            "type": "array",
+            "items": items,
+            "maxItems": t.max_size,
+        }),
+        _ => items,
+    }
+}
+
+/// Converts a default value parsed by `rclrust-msg-parser` into JSON.
+fn default_value(member: &Member) -> Option<Value> {
+    let default = member.default.as_deref()?;
+    let value_type = member.r#type.clone().inner_type();
+    match member.r#type {
+        MemberType::Array(_) | MemberType::Sequence(_) | MemberType::BoundedSequence(_) => {
+            literal::parse_sequence(default)?
+                .into_iter()
+                .map(|s| default_scalar(&value_type, s))
+                .collect::<Option<_>>()
+                .map(Value::Array)
+        }
+        _ => default_scalar(&value_type, default),
+    }
+}
+
+fn default_scalar(value_type: &NestableType, s: &str) -> Option<Value> {
+    Some(match value_type {
+        NestableType::BasicType(BasicType::Bool) => Value::Bool(s.parse().ok()?),
+        NestableType::BasicType(BasicType::F32 | BasicType::F64) => {
+            Value::Number(Number::from_f64(s.parse().ok()?)?)
+        }
+        NestableType::BasicType(BasicType::U64) => s.parse::<u64>().ok()?.into(),
+        NestableType::BasicType(_) => s.parse::<i64>().ok()?.into(),
+        NestableType::GenericString(_) => literal::parse_string(s)?.into(),
+        _ => return None,
+    })
+}
+
+/// Returns the schema of `msg` in `namespace` of its package.
+pub fn message_schema(msg: &Message, namespace: &str) -> Value {
+    let properties = msg
+        .members
+        .iter()
+        .map(|member| {
+            let mut schema = member_type_schema(&member.r#type, &msg.package, namespace);
+            if let (Some(default), Value::Object(schema)) = (default_value(member), &mut schema) {
+                schema.insert("default".into(), default);
+            }
+            (member.name.clone(), schema)
+        })
+        .collect::<Map<_, _>>();
+
+    json!({
+        "$schema": SCHEMA,
+        "title": format!("{}/{}/{}", msg.package, namespace, msg.name),
+        "type": "object",
+        "properties": properties,
+        "additionalProperties": false,
+    })
+}
+
+/// Renders the interfaces of `package` as JSON Schema documents keyed by their [`schema_path`].
+pub fn render_json_schemas(package: &str, msgs: &RosPackageMsgs) -> BTreeMap<String, String> {
+    let mut messages = Vec::new();
+    messages.extend(msgs.msgs.iter().map(|msg| ("msg", msg)));
+    for srv in &msgs.srvs {
+        messages.extend([("srv", &srv.request), ("srv", &srv.response)]);
+    }
+    for action in &msgs.actions {
+        messages.extend([
+            ("action", &action.goal),
+            ("action", &action.result),
+            ("action", &action.feedback),
+        ]);
+    }
+
+    messages
+        .into_iter()
+        .map(|(namespace, msg)| {
+            let schema = message_schema(msg, namespace);
+            (
+                schema_path(package, namespace, &msg.name),
+                serde_json::to_string_pretty(&schema).unwrap() + "\n",
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rclrust_msg_types::{Array, BoundedSequence, NamespacedType};
+
+    use super::*;
+
+    #[test]
+    fn test_member_type_schema() {
+        assert_eq!(
+            member_type_schema(&BasicType::I8.into(), "pkg", "msg"),
+            json!({ "type": "integer", "minimum": -128, "maximum": 127 })
+        );
+        assert_eq!(
+            member_type_schema(
+                &Array {
+                    value_type: GenericString::BoundedWString(3).into(),
+                    size: 2,
+                }
+                .into(),
+                "pkg",
+                "msg"
+            ),
+            json!({
+                "type": "array",
+                "items": { "type": "string", "maxLength": 3 },
+                "minItems": 2,
+                "maxItems": 2,
+            })
+        );
+        assert_eq!(
+            member_type_schema(
+                &BoundedSequence {
+                    value_type: NamespacedType {
+                        package: "builtin_interfaces".into(),
+                        namespace: "msg".into(),
+                        name: "Time".into(),
+                    }
+                    .into(),
+                    max_size: 4,
+                }
+                .into(),
+                "pkg",
+                "srv"
+            ),
+            json!({
+                "type": "array",
+                "items": { "$ref": "../../builtin_interfaces/msg/Time.json" },
+                "maxItems": 4,
+            })
+        );
+    }
+
+    #[test]
+    fn test_default_value() {
+        let member = |r#type: MemberType, default: &str| Member {
+            name: "value".into(),
+            r#type,
+            default: Some(default.into()),
+        };
+        assert_eq!(
+            default_value(&member(BasicType::F64.into(), "1.5")),
+            Some(json!(1.5))
+        );
+        assert_eq!(
+            default_value(&member(BasicType::U64.into(), "18446744073709551615")),
+            Some(json!(u64::MAX))
+        );
+        assert_eq!(
+            default_value(&member(
+                Array {
+                    value_type: GenericString::String.into(),
+                    size: 2,
+                }
+                .into(),
+                r##"[r#"a, b"#, r#""#]"##
+            )),
+            Some(json!(["a, b", ""]))
+        );
+    }
+}
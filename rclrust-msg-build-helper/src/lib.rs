@@ -6,4 +6,11 @@
 )]
 
 pub mod codegen;
+pub mod idl;
+pub mod json_schema;
 pub mod parse;
+pub mod proto;
+// sailfish inlines the included templates into a single `render_once` for each package
+#[allow(clippy::large_stack_frames)]
+pub mod render;
+pub mod typescript;
@@ -1,11 +1,12 @@
 use std::collections::HashMap;
+use std::fmt::Write as _;
 use std::fs::{self, File};
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
 use anyhow::Result;
 use rclrust_msg_parser::{parse_action_file, parse_message_file, parse_service_file};
-use rclrust_msg_types::{Action, Message, Service};
+use rclrust_msg_types::{literal, Action, Message, Service};
 
 #[derive(Debug, Clone)]
 pub struct RosPackageMsgs {
@@ -23,9 +24,28 @@ impl RosPackageMsgs {
         }
     }
 
-    fn is_empty(&self) -> bool {
+    const fn is_empty(&self) -> bool {
         self.msgs.is_empty() && self.srvs.is_empty() && self.actions.is_empty()
     }
+
+    /// Returns the message of `name` in `namespace`, including the requests and the responses of
+    /// services and the goals, the results and the feedbacks of actions.
+    pub fn message(&self, namespace: &str, name: &str) -> Option<&Message> {
+        match namespace {
+            "msg" => self.msgs.iter().find(|msg| msg.name == name),
+            "srv" => self
+                .srvs
+                .iter()
+                .flat_map(|srv| [&srv.request, &srv.response])
+                .find(|msg| msg.name == name),
+            "action" => self
+                .actions
+                .iter()
+                .flat_map(|action| [&action.goal, &action.result, &action.feedback])
+                .find(|msg| msg.name == name),
+            _ => None,
+        }
+    }
 }
 
 pub type RosPackageMsgsMap = HashMap<String, RosPackageMsgs>;
@@ -129,6 +149,45 @@ pub fn get_packages_msgs(paths: &[&Path]) -> Result<RosPackageMsgsMap> {
         }))
 }
 
+/// Parses the interface files in the `msg`, `srv` and `action` directories of `dir`.
+///
+/// `dir` is usually the source directory of a package or `share/<package>` of an ament prefix.
+pub fn get_package_msgs_from_dir<P: AsRef<Path>>(package: &str, dir: P) -> Result<RosPackageMsgs> {
+    let mut msgs = RosPackageMsgs::new();
+    for &namespace in NAMESPACES {
+        let mut paths = match fs::read_dir(dir.as_ref().join(namespace)) {
+            Ok(paths) => paths
+                .map(|path| Ok(path?.path()))
+                .collect::<Result<Vec<_>>>()?,
+            Err(_) => continue,
+        };
+        paths.retain(|path| path.extension().is_some_and(|ext| ext == namespace));
+        paths.sort();
+
+        for path in paths {
+            match namespace {
+                "msg" => msgs.msgs.push(parse_message_file(package, path)?),
+                "srv" => msgs.srvs.push(parse_service_file(package, path)?),
+                "action" => msgs.actions.push(parse_action_file(package, path)?),
+                _ => unreachable!(),
+            }
+        }
+    }
+    Ok(msgs)
+}
+
+/// Converts a value parsed by `rclrust-msg-parser` back into the syntax of interface files.
+pub fn to_msg_literal(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    while let Some((before, string, after)) = literal::split_string(rest) {
+        write!(out, "{}{:?}", before, string).unwrap();
+        rest = after;
+    }
+    out.push_str(rest);
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -170,4 +229,14 @@ mod tests {
         assert!(parse_line("srv/Test.test").is_none());
         assert!(parse_line("action/Test.test").is_none());
     }
+
+    #[test]
+    fn msg_literal() {
+        assert_eq!(to_msg_literal("1.5"), "1.5");
+        assert_eq!(to_msg_literal(r##"r#"a"#"##), r#""a""#);
+        assert_eq!(
+            to_msg_literal(r##"[r#"a"#, r#"b "c""#]"##),
+            r#"["a", "b \"c\""]"#
+        );
+    }
 }
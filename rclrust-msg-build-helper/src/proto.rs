@@ -0,0 +1,278 @@
+//! Export of the interfaces as Protocol Buffers schemas
+//!
+//! Each namespace of a ROS package becomes a `<package>/<namespace>.proto` file with the protobuf
+//! package `<package>.<namespace>`, since a message and a service may share their name.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+
+use heck::SnakeCase;
+use rclrust_msg_types::{BasicType, Distro, MemberType, Message, NestableType, Service};
+
+use crate::parse::{to_msg_literal, RosPackageMsgs};
+
+/// Returns the scalar type of protobuf representing `t`.
+pub const fn scalar_type(t: BasicType) -> &'static str {
+    match t {
+        BasicType::I8 | BasicType::I16 | BasicType::I32 => "int32",
+        BasicType::U8 | BasicType::U16 | BasicType::U32 | BasicType::Char | BasicType::Byte => {
+            "uint32"
+        }
+        BasicType::I64 => "int64",
+        BasicType::U64 => "uint64",
+        BasicType::F32 => "float",
+        BasicType::F64 => "double",
+        BasicType::Bool => "bool",
+    }
+}
+
+/// Returns `true` if the member is an array or a sequence of octets, which is mapped to `bytes`.
+pub const fn is_bytes(r#type: &MemberType) -> bool {
+    let value_type = match r#type {
+        MemberType::Array(t) => &t.value_type,
+        MemberType::Sequence(t) => &t.value_type,
+        MemberType::BoundedSequence(t) => &t.value_type,
+        _ => return false,
+    };
+    matches!(
+        value_type,
+        NestableType::BasicType(BasicType::U8 | BasicType::Char | BasicType::Byte)
+    )
+}
+
+fn nestable_type(t: &NestableType, package: &str) -> String {
+    match t {
+        NestableType::BasicType(t) => scalar_type(*t).into(),
+        NestableType::NamedType(t) => format!("{}.msg.{}", package, t.0),
+        NestableType::NamespacedType(t) => format!("{}.{}.{}", t.package, t.namespace, t.name),
+        NestableType::GenericString(_) => "string".into(),
+    }
+}
+
+/// Returns the type of the field of protobuf representing a member of type `t`.
+pub fn field_type(t: &MemberType, package: &str) -> String {
+    if is_bytes(t) {
+        return "bytes".into();
+    }
+    let inner = nestable_type(&t.clone().inner_type(), package);
+    match t {
+        MemberType::Array(_) | MemberType::Sequence(_) | MemberType::BoundedSequence(_) => {
+            format!("repeated {}", inner)
+        }
+        _ => inner,
+    }
+}
+
+/// Returns the path of the schema of `namespace` in `package`, e.g. `std_msgs/msg.proto`.
+pub fn schema_path(package: &str, namespace: &str) -> String {
+    format!("{}/{}.proto", package, namespace)
+}
+
+/// A schema of the interfaces in a namespace of a package
+struct Schema<'a> {
+    package: &'a str,
+    namespace: &'a str,
+    imports: BTreeSet<String>,
+    body: String,
+}
+
+impl<'a> Schema<'a> {
+    const fn new(package: &'a str, namespace: &'a str) -> Self {
+        Self {
+            package,
+            namespace,
+            imports: BTreeSet::new(),
+            body: String::new(),
+        }
+    }
+
+    fn message(&mut self, msg: &Message, comment: &str) {
+        let out = &mut self.body;
+        writeln!(out, "\n// {}", comment).unwrap();
+        writeln!(out, "message {} {{", msg.name).unwrap();
+        for constant in &msg.constants {
+            writeln!(
+                out,
+                "  // {} {} = {}",
+                constant.r#type,
+                constant.name,
+                to_msg_literal(&constant.value)
+            )
+            .unwrap();
+        }
+        for (i, member) in msg.members.iter().enumerate() {
+            write!(
+                out,
+                "  {} {} = {};",
+                field_type(&member.r#type, &msg.package),
+                member.name,
+                i + 1
+            )
+            .unwrap();
+            if let Some(default) = &member.default {
+                write!(out, "  // default: {}", to_msg_literal(default)).unwrap();
+            }
+            out.push('\n');
+
+            let import = match member.r#type.clone().inner_type() {
+                NestableType::NamedType(_) => schema_path(&msg.package, "msg"),
+                NestableType::NamespacedType(t) => schema_path(&t.package, &t.namespace),
+                _ => continue,
+            };
+            if import != schema_path(self.package, self.namespace) {
+                self.imports.insert(import);
+            }
+        }
+        out.push_str("}\n");
+    }
+
+    /// Adds the messages of `srvs` and a service with a rpc for each of them.
+    fn service(&mut self, name: &str, srvs: &[(&str, Service)], comment: &str) {
+        for (rpc, srv) in srvs {
+            let rpc = if srvs.len() == 1 {
+                String::new()
+            } else {
+                format!(" {}", rpc.to_snake_case().replace('_', " "))
+            };
+            self.message(&srv.request, &format!("{}{} request", comment, rpc));
+            self.message(&srv.response, &format!("{}{} response", comment, rpc));
+        }
+        let out = &mut self.body;
+        writeln!(out, "\n// {}", comment).unwrap();
+        writeln!(out, "service {} {{", name).unwrap();
+        for (rpc, srv) in srvs {
+            writeln!(
+                out,
+                "  rpc {}({}) returns ({});",
+                rpc, srv.request.name, srv.response.name
+            )
+            .unwrap();
+        }
+        out.push_str("}\n");
+    }
+
+    fn render(self) -> String {
+        let mut out = format!(
+            "// Generated from the ROS interfaces in {0}/{1}\n\
+             syntax = \"proto3\";\n\
+             \n\
+             package {0}.{1};\n",
+            self.package, self.namespace
+        );
+        if !self.imports.is_empty() {
+            out.push('\n');
+            for import in self.imports {
+                writeln!(out, "import \"{}\";", import).unwrap();
+            }
+        }
+        out + &self.body
+    }
+}
+
+/// Renders the interfaces of `package` as schemas of protobuf.
+///
+/// Returns a schema for each namespace with interfaces, keyed by its [`schema_path`].
+pub fn render_proto(package: &str, msgs: &RosPackageMsgs) -> BTreeMap<String, String> {
+    let mut schemas = BTreeMap::new();
+
+    if !msgs.msgs.is_empty() {
+        let mut schema = Schema::new(package, "msg");
+        for msg in &msgs.msgs {
+            schema.message(msg, &format!("{}/msg/{}", package, msg.name));
+        }
+        schemas.insert(schema_path(package, "msg"), schema.render());
+    }
+
+    if !msgs.srvs.is_empty() {
+        let mut schema = Schema::new(package, "srv");
+        for srv in &msgs.srvs {
+            let comment = format!("{}/srv/{}", package, srv.name);
+            schema.service(&srv.name, &[("Call", srv.clone())], &comment);
+        }
+        schemas.insert(schema_path(package, "srv"), schema.render());
+    }
+
+    if !msgs.actions.is_empty() {
+        // Events of services are not exported, so the synthesized types are the same in all distros
+        let distro = Distro::Foxy;
+        let mut schema = Schema::new(package, "action");
+        for action in &msgs.actions {
+            let comment = format!("{}/action/{}", package, action.name);
+            schema.message(&action.goal, &format!("{} goal", comment));
+            schema.message(&action.result, &format!("{} result", comment));
+            schema.message(&action.feedback, &format!("{} feedback", comment));
+            schema.message(
+                &action.feedback_message_msg(distro),
+                &format!("{} feedback message", comment),
+            );
+            let srvs = [
+                ("SendGoal", action.send_goal_srv(distro)),
+                ("GetResult", action.get_result_srv(distro)),
+            ];
+            schema.service(&action.name, &srvs, &comment);
+        }
+        schemas.insert(schema_path(package, "action"), schema.render());
+    }
+
+    schemas
+}
+
+#[cfg(test)]
+mod tests {
+    use rclrust_msg_types::{Array, GenericString, Member, Sequence};
+
+    use super::*;
+
+    #[test]
+    fn test_field_type() {
+        let octets = MemberType::from(Sequence {
+            value_type: BasicType::Byte.into(),
+        });
+        assert!(is_bytes(&octets));
+        assert_eq!(field_type(&octets, "pkg"), "bytes");
+        assert!(!is_bytes(&BasicType::U8.into()));
+        assert_eq!(field_type(&BasicType::I8.into(), "pkg"), "int32");
+        assert_eq!(
+            field_type(
+                &Array {
+                    value_type: GenericString::WString.into(),
+                    size: 2
+                }
+                .into(),
+                "pkg"
+            ),
+            "repeated string"
+        );
+    }
+
+    #[test]
+    fn test_render_proto() {
+        let msgs = RosPackageMsgs {
+            msgs: vec![Message {
+                package: "pkg".into(),
+                name: "Point".into(),
+                members: vec![Member {
+                    name: "x".into(),
+                    r#type: BasicType::F64.into(),
+                    default: Some("1.5".into()),
+                }],
+                constants: vec![],
+            }],
+            srvs: vec![],
+            actions: vec![],
+        };
+        assert_eq!(
+            render_proto("pkg", &msgs)["pkg/msg.proto"],
+            "// Generated from the ROS interfaces in pkg/msg
+syntax = \"proto3\";
+
+package pkg.msg;
+
+// pkg/msg/Point
+message Point {
+  double x = 1;  // default: 1.5
+}
+"
+        );
+    }
+}
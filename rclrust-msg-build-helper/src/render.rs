@@ -0,0 +1,488 @@
+//! Rendering of the templates into Rust code
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use heck::SnakeCase;
+pub use rclrust_msg_types::Distro;
+use rclrust_msg_types::NestableType;
+use sailfish::runtime::RenderError;
+use sailfish::TemplateOnce;
+
+use crate::codegen::{self, Layout};
+use crate::parse::{RosPackageMsgs, RosPackageMsgsMap};
+
+#[derive(Debug, TemplateOnce)]
+#[template(path = "mod.rs.stpl", delimiter = '@', escape = false)]
+struct ModuleTemplate<'a> {
+    package: &'a str,
+    msgs: &'a RosPackageMsgs,
+    packages: &'a RosPackageMsgsMap,
+    layout: Layout<'a>,
+    options: &'a Options,
+}
+
+#[derive(Debug, TemplateOnce)]
+#[template(path = "registry.rs.stpl", delimiter = '@', escape = false)]
+struct RegistryTemplate<'a> {
+    packages: &'a [String],
+    options: &'a Options,
+}
+
+#[derive(Debug, TemplateOnce)]
+#[template(path = "lib.rs.stpl", delimiter = '@', escape = false)]
+struct LibTemplate<'a> {
+    package: &'a str,
+    msgs: &'a RosPackageMsgs,
+    options: &'a Options,
+}
+
+#[derive(Debug, TemplateOnce)]
+#[template(path = "msgs.rs.stpl", delimiter = '@', escape = false)]
+struct MsgsTemplate<'a> {
+    package: &'a str,
+    msgs: &'a RosPackageMsgs,
+    packages: &'a RosPackageMsgsMap,
+    layout: Layout<'a>,
+    options: &'a Options,
+}
+
+#[derive(Debug, TemplateOnce)]
+#[template(path = "srvs.rs.stpl", delimiter = '@', escape = false)]
+struct SrvsTemplate<'a> {
+    package: &'a str,
+    msgs: &'a RosPackageMsgs,
+    packages: &'a RosPackageMsgsMap,
+    layout: Layout<'a>,
+    options: &'a Options,
+}
+
+#[derive(Debug, TemplateOnce)]
+#[template(path = "actions.rs.stpl", delimiter = '@', escape = false)]
+struct ActionsTemplate<'a> {
+    package: &'a str,
+    msgs: &'a RosPackageMsgs,
+    packages: &'a RosPackageMsgsMap,
+    layout: Layout<'a>,
+    options: &'a Options,
+}
+
+const BUILD_RS: &str = r#"fn main() {
+    println!("cargo:rerun-if-env-changed=AMENT_PREFIX_PATH");
+
+    let ament_prefix_paths =
+        std::env::var("AMENT_PREFIX_PATH").expect("$AMENT_PREFIX_PATH is supposed to be set.");
+    for ament_prefix_path in ament_prefix_paths.split(':') {
+        println!("cargo:rustc-link-search=native={}/lib", ament_prefix_path);
+    }
+}
+"#;
+
+/// Options of the code generated by [`generate`]
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// The path to the module which the generated code is included in, e.g. `crate` or
+    /// `crate::msgs`
+    ///
+    /// The generated tests expect a `registry()` function in this module, which returns the global
+    /// registry after `register_all`, as `rclrust-msg` defines.
+    pub crate_path: String,
+    /// Traits derived by the messages in addition to `Debug`, `Clone` and `PartialEq`
+    pub derives: Vec<String>,
+    /// How the optional parts of the generated code are enabled
+    pub features: Features,
+    /// Whether to emit doc comments
+    pub docs: bool,
+    /// The distro whose interfaces are generated, which determines the types synthesized for
+    /// services and actions
+    pub distro: Distro,
+    /// The directory of hand-written code to add to the generated types, e.g. `impl` blocks of
+    /// helpers
+    ///
+    /// The contents of `<ext_dir>/<package>/<namespace>/<Type>.rs` are inserted into the module
+    /// defining `<package>/<namespace>/<Type>`, next to the type and the imports of the generated
+    /// code.
+    pub ext_dir: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            crate_path: "crate".into(),
+            derives: Vec::new(),
+            features: Features::Cargo,
+            docs: true,
+            distro: Distro::Foxy,
+            ext_dir: None,
+        }
+    }
+}
+
+impl Options {
+    /// Returns the `cfg` predicate enabling the parts of the generated code for `feature`.
+    pub fn cfg(&self, feature: &str) -> String {
+        match self.features {
+            Features::Cargo => format!("feature = \"{}\"", feature),
+            Features::Fixed(ref features) if features.iter().any(|f| f == feature) => {
+                "all()".into()
+            }
+            Features::Fixed(_) => "any()".into(),
+        }
+    }
+
+    /// Returns the hand-written code of `<package>/<namespace>/<name>` in [`Options::ext_dir`], or
+    /// `None` if there is no such file.
+    pub fn ext_fragment(
+        &self,
+        package: &str,
+        namespace: &str,
+        name: &str,
+    ) -> io::Result<Option<String>> {
+        let path = match self.ext_dir {
+            Some(ref ext_dir) => ext_dir
+                .join(package)
+                .join(namespace)
+                .join(format!("{}.rs", name)),
+            None => return Ok(None),
+        };
+        match fs::read_to_string(&path) {
+            Ok(code) => Ok(Some(code)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(io::Error::new(
+                e.kind(),
+                format!("failed to read {}: {}", path.display(), e),
+            )),
+        }
+    }
+
+    /// [`Options::ext_fragment`] for the templates
+    pub(crate) fn ext_code(
+        &self,
+        package: &str,
+        namespace: &str,
+        name: &str,
+    ) -> Result<Option<String>, RenderError> {
+        self.ext_fragment(package, namespace, name)
+            .map_err(|e| RenderError::new(&e.to_string()))
+    }
+}
+
+/// How the optional parts of the generated code, i.e. `chrono`, `prost`, `proptest`, `quickcheck`
+/// and `mock-rosidl`, are enabled
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Features {
+    /// By the cargo features of the same names of the crate including the code
+    Cargo,
+    /// By this list regardless of the cargo features
+    Fixed(Vec<String>),
+}
+
+/// Generates the code of all `packages` as modules, followed by `register_all`.
+///
+/// The code is meant to be `include!`d at [`Options::crate_path`].
+pub fn generate(packages: &RosPackageMsgsMap, options: &Options) -> Result<String> {
+    let mut sorted = packages.iter().collect::<Vec<_>>();
+    sorted.sort_by_key(|&(package, _)| package);
+
+    let mut out = String::new();
+    for (package, msgs) in &sorted {
+        out.push_str(&render_module(package, msgs, packages, options)?);
+    }
+    out.push_str(&render_registry(
+        &sorted
+            .iter()
+            .map(|&(package, _)| package.clone())
+            .collect::<Vec<_>>(),
+        options,
+    ));
+    Ok(out)
+}
+
+/// Renders `package` as a module of the module containing all the packages.
+///
+/// The types nested in `msgs` are looked up in `packages` to find the plain messages.
+pub fn render_module(
+    package: &str,
+    msgs: &RosPackageMsgs,
+    packages: &RosPackageMsgsMap,
+    options: &Options,
+) -> Result<String> {
+    Ok(ModuleTemplate {
+        package,
+        msgs,
+        packages,
+        layout: Layout::Modules(&options.crate_path),
+        options,
+    }
+    .render_once()?)
+}
+
+/// Renders `register_all`, which registers the modules of `packages`.
+pub fn render_registry(packages: &[String], options: &Options) -> String {
+    RegistryTemplate { packages, options }
+        .render_once()
+        .unwrap()
+}
+
+/// Options of the crates generated by [`render_crate`]
+#[derive(Debug, Clone)]
+pub struct CrateOptions {
+    /// The version of the generated crates
+    pub version: String,
+    /// The dependency of `rclrust-msg-core`, e.g. `{ path = "../rclrust-msg-core" }`
+    pub core_dependency: String,
+    /// The distro whose interfaces are generated
+    pub distro: Distro,
+    /// The directory of hand-written code to add to the generated types, as
+    /// [`Options::ext_dir`]
+    pub ext_dir: Option<PathBuf>,
+}
+
+impl Default for CrateOptions {
+    fn default() -> Self {
+        Self {
+            version: "0.1.0".into(),
+            core_dependency: format!("\"{}\"", env!("CARGO_PKG_VERSION")),
+            distro: Distro::Foxy,
+            ext_dir: None,
+        }
+    }
+}
+
+/// Returns the other packages whose types are used by `package` in `distro`.
+pub fn dependencies(package: &str, msgs: &RosPackageMsgs, distro: Distro) -> BTreeSet<String> {
+    let mut messages = msgs.msgs.to_vec();
+    for srv in &msgs.srvs {
+        messages.extend([srv.request.clone(), srv.response.clone()]);
+        messages.extend(srv.event_msg("srv", distro));
+    }
+    for action in &msgs.actions {
+        messages.extend([
+            action.goal.clone(),
+            action.result.clone(),
+            action.feedback.clone(),
+            action.feedback_message_msg(distro),
+        ]);
+        for srv in [action.send_goal_srv(distro), action.get_result_srv(distro)] {
+            messages.extend(srv.event_msg("action", distro));
+            messages.extend([srv.request, srv.response]);
+        }
+    }
+
+    messages
+        .iter()
+        .flat_map(|msg| &msg.members)
+        .filter_map(|member| match member.r#type.clone().inner_type() {
+            NestableType::NamespacedType(t) if t.package != package => Some(t.package),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Renders `package` as a crate of its own.
+///
+/// The types nested in `msgs` are looked up in `packages` to find the plain messages. Returns the
+/// contents of the files keyed by their paths relative to the crate root.
+pub fn render_crate(
+    package: &str,
+    msgs: &RosPackageMsgs,
+    packages: &RosPackageMsgsMap,
+    options: &CrateOptions,
+) -> Result<BTreeMap<&'static str, String>> {
+    let layout = Layout::Crates;
+    let code_options = Options {
+        distro: options.distro,
+        ext_dir: options.ext_dir.clone(),
+        ..Options::default()
+    };
+    let mut files = BTreeMap::new();
+    files.insert("Cargo.toml", cargo_toml(package, msgs, options));
+    files.insert("build.rs", BUILD_RS.into());
+    files.insert(
+        "src/lib.rs",
+        tidy(
+            LibTemplate {
+                package,
+                msgs,
+                options: &code_options,
+            }
+            .render_once()?,
+        ),
+    );
+    if !msgs.msgs.is_empty() {
+        let ctx = MsgsTemplate {
+            package,
+            msgs,
+            packages,
+            layout,
+            options: &code_options,
+        };
+        files.insert("src/msg.rs", tidy(ctx.render_once()?));
+    }
+    if !msgs.srvs.is_empty() {
+        let ctx = SrvsTemplate {
+            package,
+            msgs,
+            packages,
+            layout,
+            options: &code_options,
+        };
+        files.insert("src/srv.rs", tidy(ctx.render_once()?));
+    }
+    if !msgs.actions.is_empty() {
+        let ctx = ActionsTemplate {
+            package,
+            msgs,
+            packages,
+            layout,
+            options: &code_options,
+        };
+        files.insert("src/action.rs", tidy(ctx.render_once()?));
+    }
+    Ok(files)
+}
+
+fn cargo_toml(package: &str, msgs: &RosPackageMsgs, options: &CrateOptions) -> String {
+    let mut out = format!(
+        "[package]\n\
+         name = \"{0}\"\n\
+         version = \"{1}\"\n\
+         edition = \"2018\"\n\
+         description = \"Rust types of the ROS interfaces in {0}\"\n\
+         \n\
+         [dependencies]\n\
+         rclrust-msg-core = {2}\n",
+        package, options.version, options.core_dependency
+    );
+    let dependencies = dependencies(package, msgs, options.distro);
+    for dependency in &dependencies {
+        writeln!(out, "{0} = {{ path = \"../{0}\" }}", dependency).unwrap();
+    }
+    out.push_str(
+        "prost = { version = \"0.11\", optional = true }\n\
+         proptest = { version = \"1.0\", optional = true }\n\
+         quickcheck = { version = \"1.0\", optional = true }\n",
+    );
+
+    let features = |feature: &str, own: &[&str]| {
+        own.iter()
+            .map(|f| format!("\"{}\"", f))
+            .chain(
+                dependencies
+                    .iter()
+                    .map(|d| format!("\"{}/{}\"", d, feature)),
+            )
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    write!(
+        out,
+        "\n[features]\n\
+         chrono = [{}]\n\
+         prost = [{}]\n\
+         mock-rosidl = [{}]\n\
+         proptest = [{}]\n\
+         quickcheck = [{}]\n",
+        features("chrono", &["rclrust-msg-core/chrono"]),
+        features("prost", &["dep:prost"]),
+        features("mock-rosidl", &["rclrust-msg-core/mock-rosidl"]),
+        features("proptest", &["dep:proptest", "rclrust-msg-core/proptest"]),
+        features(
+            "quickcheck",
+            &["dep:quickcheck", "rclrust-msg-core/quickcheck"]
+        )
+    )
+    .unwrap();
+    out
+}
+
+/// Removes the blank lines left by the template tags.
+fn tidy(code: String) -> String {
+    let mut out = String::with_capacity(code.len());
+    let mut blank = true;
+    for line in code.lines() {
+        if line.trim().is_empty() {
+            if !blank {
+                out.push('\n');
+            }
+            blank = true;
+        } else {
+            if line.trim_start().starts_with('}') && out.ends_with("\n\n") {
+                out.pop();
+            }
+            out.push_str(line);
+            out.push('\n');
+            blank = line.ends_with('{');
+        }
+    }
+    if out.ends_with("\n\n") {
+        out.pop();
+    }
+    out
+}
+
+/// Writes a crate for each of `packages` and the packages they depend on into `out_dir`.
+///
+/// All the packages are written if `packages` is empty. Returns the names of the written packages.
+pub fn write_crates(
+    msgs_map: &RosPackageMsgsMap,
+    packages: &[&str],
+    out_dir: &Path,
+    options: &CrateOptions,
+) -> Result<Vec<String>> {
+    let mut pending = if packages.is_empty() {
+        msgs_map.keys().cloned().collect::<Vec<_>>()
+    } else {
+        packages
+            .iter()
+            .map(|&package| package.to_string())
+            .collect()
+    };
+    let mut resolved = BTreeMap::new();
+    while let Some(package) = pending.pop() {
+        if resolved.contains_key(&package) {
+            continue;
+        }
+        let msgs = msgs_map
+            .get(&package)
+            .ok_or_else(|| anyhow!("Unknown package: {}", package))?;
+        for dependency in dependencies(&package, msgs, options.distro) {
+            if !msgs_map.contains_key(&dependency) {
+                return Err(anyhow!(
+                    "{} depends on unknown package: {}",
+                    package,
+                    dependency
+                ));
+            }
+            pending.push(dependency);
+        }
+        resolved.insert(package, msgs);
+    }
+
+    for (package, msgs) in &resolved {
+        let crate_dir = out_dir.join(package);
+        for (path, contents) in render_crate(package, msgs, msgs_map, options)? {
+            let path = crate_dir.join(path);
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(path, contents)?;
+        }
+    }
+    Ok(resolved.into_keys().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tidy() {
+        assert_eq!(
+            tidy("\nmod a {\n\n    fn b() {}\n\n\n    fn c() {}\n\n}\n\n".into()),
+            "mod a {\n    fn b() {}\n\n    fn c() {}\n}\n"
+        );
+    }
+}
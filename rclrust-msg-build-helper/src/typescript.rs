@@ -0,0 +1,204 @@
+//! Generation of TypeScript type definitions of the interfaces
+//!
+//! Each package becomes `<package>.d.ts` with the namespaces `msg`, `srv` and `action`, e.g.
+//! `std_msgs.msg.Header`. Constants are declared in a namespace merged with their interface.
+
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+
+use rclrust_msg_types::{BasicType, ConstantType, MemberType, Message, NestableType};
+
+use crate::parse::{to_msg_literal, RosPackageMsgs};
+use crate::proto::is_bytes;
+
+/// Returns the path of the type definitions of `package`, e.g. `std_msgs.d.ts`.
+pub fn definition_path(package: &str) -> String {
+    format!("{}.d.ts", package)
+}
+
+fn nestable_type(t: &NestableType) -> String {
+    match t {
+        NestableType::BasicType(BasicType::Bool) => "boolean".into(),
+        NestableType::BasicType(_) => "number".into(),
+        NestableType::NamedType(t) => format!("msg.{}", t.0),
+        NestableType::NamespacedType(t) => format!("{}.{}.{}", t.package, t.namespace, t.name),
+        NestableType::GenericString(_) => "string".into(),
+    }
+}
+
+fn member_type(t: &MemberType, package: &str) -> String {
+    if is_bytes(t) {
+        return "number[] | Uint8Array".into();
+    }
+    let inner = match t.clone().inner_type() {
+        NestableType::NamespacedType(t) if t.package == package => {
+            format!("{}.{}", t.namespace, t.name)
+        }
+        inner => nestable_type(&inner),
+    };
+    match t {
+        MemberType::Array(_) | MemberType::Sequence(_) | MemberType::BoundedSequence(_) => {
+            format!("{}[]", inner)
+        }
+        _ => inner,
+    }
+}
+
+fn write_interface(out: &mut String, msg: &Message, imports: &mut BTreeSet<String>) {
+    writeln!(out, "\n  export interface {} {{", msg.name).unwrap();
+    for member in &msg.members {
+        writeln!(
+            out,
+            "    {}: {};",
+            member.name,
+            member_type(&member.r#type, &msg.package)
+        )
+        .unwrap();
+        if let NestableType::NamespacedType(t) = member.r#type.clone().inner_type() {
+            if t.package != msg.package {
+                imports.insert(t.package);
+            }
+        }
+    }
+    out.push_str("  }\n");
+
+    if msg.constants.is_empty() {
+        return;
+    }
+    writeln!(out, "\n  export namespace {} {{", msg.name).unwrap();
+    for constant in &msg.constants {
+        let value = to_msg_literal(&constant.value);
+        match constant.r#type {
+            ConstantType::PrimitiveArray(_) => {
+                writeln!(
+                    out,
+                    "    export const {}: readonly {};",
+                    constant.name, value
+                )
+            }
+            _ => writeln!(out, "    export const {} = {};", constant.name, value),
+        }
+        .unwrap();
+    }
+    out.push_str("  }\n");
+}
+
+/// Renders the type definitions of the interfaces in `package`.
+pub fn render_typescript(package: &str, msgs: &RosPackageMsgs) -> String {
+    let mut imports = BTreeSet::new();
+    let mut body = String::new();
+    let mut namespace = |name: &str, messages: Vec<&Message>| {
+        if messages.is_empty() {
+            return;
+        }
+        write!(body, "\nexport namespace {} {{", name).unwrap();
+        for msg in messages {
+            write_interface(&mut body, msg, &mut imports);
+        }
+        body.push_str("}\n");
+    };
+
+    namespace("msg", msgs.msgs.iter().collect());
+    namespace(
+        "srv",
+        msgs.srvs
+            .iter()
+            .flat_map(|srv| [&srv.request, &srv.response])
+            .collect(),
+    );
+    namespace(
+        "action",
+        msgs.actions
+            .iter()
+            .flat_map(|action| [&action.goal, &action.result, &action.feedback])
+            .collect(),
+    );
+
+    let mut out = format!("// Generated from the ROS interfaces in {}\n", package);
+    if !imports.is_empty() {
+        out.push('\n');
+        for import in imports {
+            writeln!(out, "import * as {0} from \"./{0}\";", import).unwrap();
+        }
+    }
+    out + &body
+}
+
+#[cfg(test)]
+mod tests {
+    use rclrust_msg_types::{Array, Constant, Member, NamespacedType, Sequence};
+
+    use super::*;
+
+    #[test]
+    fn test_member_type() {
+        let time = NamespacedType {
+            package: "builtin_interfaces".into(),
+            namespace: "msg".into(),
+            name: "Time".into(),
+        };
+        assert_eq!(
+            member_type(&time.clone().into(), "std_msgs"),
+            "builtin_interfaces.msg.Time"
+        );
+        assert_eq!(member_type(&time.into(), "builtin_interfaces"), "msg.Time");
+        assert_eq!(
+            member_type(
+                &Sequence {
+                    value_type: BasicType::Byte.into()
+                }
+                .into(),
+                "pkg"
+            ),
+            "number[] | Uint8Array"
+        );
+        assert_eq!(
+            member_type(
+                &Array {
+                    value_type: BasicType::I64.into(),
+                    size: 2
+                }
+                .into(),
+                "pkg"
+            ),
+            "number[]"
+        );
+    }
+
+    #[test]
+    fn test_render_typescript() {
+        let msgs = RosPackageMsgs {
+            msgs: vec![Message {
+                package: "pkg".into(),
+                name: "Mode".into(),
+                members: vec![Member {
+                    name: "value".into(),
+                    r#type: BasicType::U8.into(),
+                    default: None,
+                }],
+                constants: vec![Constant {
+                    name: "AUTO".into(),
+                    r#type: BasicType::U8.into(),
+                    value: "1".into(),
+                }],
+            }],
+            srvs: vec![],
+            actions: vec![],
+        };
+        assert_eq!(
+            render_typescript("pkg", &msgs),
+            "// Generated from the ROS interfaces in pkg
+
+export namespace msg {
+  export interface Mode {
+    value: number;
+  }
+
+  export namespace Mode {
+    export const AUTO = 1;
+  }
+}
+"
+        );
+    }
+}
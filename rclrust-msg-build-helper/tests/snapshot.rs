@@ -0,0 +1,75 @@
+//! Snapshots of the generated code
+//!
+//! Review changes of the snapshots with `cargo insta review`, or accept them all by running the
+//! tests with `INSTA_UPDATE=always`.
+
+use std::path::{Path, PathBuf};
+
+use rclrust_msg_build_helper::parse::get_packages_msgs;
+use rclrust_msg_build_helper::render::{
+    render_crate, render_module, render_registry, CrateOptions, Distro, Options,
+};
+
+fn test_data(prefixes: &[&str]) -> Vec<PathBuf> {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/test_data");
+    prefixes.iter().map(|prefix| root.join(prefix)).collect()
+}
+
+fn assert_modules_snapshot(prefixes: &[&str], options: &Options, suffix: &str) {
+    let paths = test_data(prefixes);
+    let packages =
+        get_packages_msgs(&paths.iter().map(PathBuf::as_path).collect::<Vec<_>>()).unwrap();
+    for (package, msgs) in &packages {
+        insta::assert_snapshot!(
+            format!("{}{}", package, suffix),
+            render_module(package, msgs, &packages, options).unwrap()
+        );
+    }
+}
+
+#[test]
+fn snapshot_test_data() {
+    assert_modules_snapshot(
+        &["msg", "srv", "action", "deps", "test_msgs"],
+        &Options::default(),
+        "",
+    );
+}
+
+#[test]
+fn snapshot_codegen() {
+    assert_modules_snapshot(&["codegen"], &Options::default(), "");
+}
+
+#[test]
+fn snapshot_codegen_service_events() {
+    let options = Options {
+        distro: Distro::Jazzy,
+        ..Options::default()
+    };
+    assert_modules_snapshot(&["codegen"], &options, "_jazzy");
+}
+
+#[test]
+fn snapshot_codegen_crate() {
+    let paths = test_data(&["codegen"]);
+    let packages = get_packages_msgs(&[Path::new(&paths[0])]).unwrap();
+    let files = render_crate(
+        "codegen_test_msgs",
+        &packages["codegen_test_msgs"],
+        &packages,
+        &CrateOptions::default(),
+    )
+    .unwrap();
+    for (path, contents) in &files {
+        insta::assert_snapshot!(format!("crate_{}", path.replace('/', "_")), contents);
+    }
+}
+
+#[test]
+fn snapshot_registry() {
+    insta::assert_snapshot!(render_registry(
+        &["std_msgs".into(), "codegen_test_msgs".into()],
+        &Options::default()
+    ));
+}
@@ -1,5 +1,12 @@
 use anyhow::Result;
-use rclrust_msg_build_helper::parse::get_packages_msgs;
+use rclrust_msg_build_helper::codegen::is_plain_message;
+use rclrust_msg_build_helper::parse::{get_package_msgs_from_dir, get_packages_msgs};
+use rclrust_msg_build_helper::proto::render_proto;
+use rclrust_msg_build_helper::render::{
+    dependencies, generate, render_crate, CrateOptions, Distro, Features, Options,
+};
+use rclrust_msg_parser::msg::parse_message_string;
+use rclrust_msg_types::{MessageClass, NamespacedType};
 use std::path::PathBuf;
 
 #[test]
@@ -50,3 +57,287 @@ fn get_ros_msgs_action() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn get_ros_msgs_from_dir() -> Result<()> {
+    let test_file_path =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/test_data/srv/share/std_srvs");
+
+    let item = get_package_msgs_from_dir("std_srvs", &test_file_path)?;
+
+    assert!(item.msgs.is_empty());
+    assert_eq!(
+        item.srvs
+            .iter()
+            .map(|v| v.name.as_str())
+            .collect::<Vec<_>>(),
+        ["Empty", "SetBool", "Trigger"]
+    );
+    assert!(item.actions.is_empty());
+    assert_eq!(item.srvs[1].package, "std_srvs");
+
+    Ok(())
+}
+
+#[test]
+fn render_crate_action() -> Result<()> {
+    let test_file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/test_data/action");
+
+    let result = get_packages_msgs(&[&test_file_path])?;
+    let item = result.get("action_tutorials_interfaces").unwrap();
+
+    assert_eq!(
+        dependencies("action_tutorials_interfaces", item, Distro::Foxy)
+            .into_iter()
+            .collect::<Vec<_>>(),
+        ["builtin_interfaces", "unique_identifier_msgs"]
+    );
+
+    let files = render_crate(
+        "action_tutorials_interfaces",
+        item,
+        &result,
+        &CrateOptions::default(),
+    )?;
+    assert_eq!(
+        files.keys().copied().collect::<Vec<_>>(),
+        ["Cargo.toml", "build.rs", "src/action.rs", "src/lib.rs"]
+    );
+    assert!(files["Cargo.toml"]
+        .contains("unique_identifier_msgs = { path = \"../unique_identifier_msgs\" }\n"));
+    assert!(files["Cargo.toml"].contains(
+        "prost = [\"dep:prost\", \"builtin_interfaces/prost\", \"unique_identifier_msgs/prost\"]\n"
+    ));
+    assert!(files["Cargo.toml"].contains(
+        "mock-rosidl = [\"rclrust-msg-core/mock-rosidl\", \"builtin_interfaces/mock-rosidl\", \"unique_identifier_msgs/mock-rosidl\"]\n"
+    ));
+    assert!(files["Cargo.toml"].contains(
+        "proptest = [\"dep:proptest\", \"rclrust-msg-core/proptest\", \
+         \"builtin_interfaces/proptest\", \"unique_identifier_msgs/proptest\"]\n"
+    ));
+    assert!(files["src/lib.rs"].contains("\npub mod action;\n"));
+    assert!(files["src/action.rs"].contains("pub goal_id: ::unique_identifier_msgs::msg::UUID,"));
+    assert!(files["src/action.rs"].contains("pub goal: crate::action::Fibonacci_Goal,"));
+
+    Ok(())
+}
+
+#[test]
+fn render_crate_action_events() -> Result<()> {
+    let test_file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/test_data/action");
+
+    let result = get_packages_msgs(&[&test_file_path])?;
+    let item = result.get("action_tutorials_interfaces").unwrap();
+
+    assert_eq!(
+        dependencies("action_tutorials_interfaces", item, Distro::Jazzy)
+            .into_iter()
+            .collect::<Vec<_>>(),
+        [
+            "builtin_interfaces",
+            "service_msgs",
+            "unique_identifier_msgs"
+        ]
+    );
+
+    let files = render_crate(
+        "action_tutorials_interfaces",
+        item,
+        &result,
+        &CrateOptions {
+            distro: Distro::Jazzy,
+            ..CrateOptions::default()
+        },
+    )?;
+    assert!(files["Cargo.toml"].contains("service_msgs = { path = \"../service_msgs\" }\n"));
+    let code = &files["src/action.rs"];
+    assert!(code.contains("impl _ServiceEventT for Fibonacci_SendGoal {"));
+    assert!(code.contains("pub info: ::service_msgs::msg::ServiceEventInfo,"));
+    assert!(
+        code.contains("pub request: std::vec::Vec<crate::action::Fibonacci_GetResult_Request>,")
+    );
+    assert!(!render_crate(
+        "action_tutorials_interfaces",
+        item,
+        &result,
+        &CrateOptions::default()
+    )?["src/action.rs"]
+        .contains("_Event"));
+
+    Ok(())
+}
+
+#[test]
+fn distro() {
+    assert_eq!(Distro::parse("humble"), Some(Distro::Humble));
+    assert_eq!(Distro::parse("ardent"), None);
+    assert_eq!(Distro::Iron.to_string(), "iron");
+    assert!(!Distro::Humble.has_service_events());
+    assert!(Distro::Iron.has_service_events());
+    assert!(Distro::Rolling.has_service_events());
+}
+
+#[test]
+fn plain_messages() -> Result<()> {
+    let test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/test_data");
+    let packages = get_packages_msgs(&[
+        &test_data.join("test_msgs"),
+        &test_data.join("srv"),
+        &test_data.join("action"),
+        &test_data.join("deps"),
+    ])?;
+    let message = |package: &str, namespace: &str, name: &str| {
+        packages[package]
+            .message(namespace, name)
+            .unwrap_or_else(|| panic!("{}/{}/{}", package, namespace, name))
+    };
+    let plain =
+        |package, namespace, name| is_plain_message(message(package, namespace, name), &packages);
+
+    assert!(plain("test_msgs", "msg", "BasicTypes"));
+    assert!(plain("test_msgs", "msg", "Nested"));
+    assert!(plain("builtin_interfaces", "msg", "Time"));
+    assert!(plain("unique_identifier_msgs", "msg", "UUID"));
+    assert!(plain("std_srvs", "srv", "SetBool_Request"));
+    assert!(plain(
+        "action_tutorials_interfaces",
+        "action",
+        "Fibonacci_Goal"
+    ));
+    assert!(!plain("test_msgs", "msg", "Empty"));
+    assert!(!plain("test_msgs", "msg", "Arrays"));
+    assert!(!plain("test_msgs", "msg", "BoundedSequences"));
+    assert!(!plain("std_srvs", "srv", "SetBool_Response"));
+    assert!(!plain(
+        "action_tutorials_interfaces",
+        "action",
+        "Fibonacci_Result"
+    ));
+
+    let resolve = |t: &NamespacedType| packages[&t.package].message(&t.namespace, &t.name).cloned();
+    let class = |package, namespace, name| message(package, namespace, name).class(&resolve);
+    assert_eq!(class("test_msgs", "msg", "Nested"), MessageClass::Plain);
+    assert_eq!(
+        class("test_msgs", "msg", "BoundedSequences"),
+        MessageClass::Unbounded
+    );
+    assert_eq!(
+        class("test_msgs", "msg", "Strings"),
+        MessageClass::Unbounded
+    );
+    assert_eq!(class("test_msgs", "msg", "Empty"), MessageClass::Plain);
+    let bounded = parse_message_string(
+        "test_msgs",
+        "Bounded",
+        "string<=8 name\nBasicTypes[<=3] values\nNested nested\n",
+    )?;
+    assert_eq!(bounded.class(&resolve), MessageClass::Bounded);
+
+    let code = generate(&packages, &Options::default())?;
+    assert!(code.contains("#[repr(C)]\n#[derive(std::fmt::Debug, std::clone::Clone, std::cmp::PartialEq)]\npub struct BasicTypes {"));
+    assert!(code.contains("unsafe impl _PlainMessage for Fibonacci_SendGoal_Request {"));
+    assert!(!code.contains("unsafe impl _PlainMessage for Fibonacci_FeedbackMessage {"));
+
+    Ok(())
+}
+
+#[test]
+fn render_proto_action() -> Result<()> {
+    let test_file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/test_data/action");
+
+    let result = get_packages_msgs(&[&test_file_path])?;
+    let item = result.get("action_tutorials_interfaces").unwrap();
+
+    let schemas = render_proto("action_tutorials_interfaces", item);
+    assert_eq!(
+        schemas.keys().map(String::as_str).collect::<Vec<_>>(),
+        ["action_tutorials_interfaces/action.proto"]
+    );
+    let proto = &schemas["action_tutorials_interfaces/action.proto"];
+    assert!(proto.contains("\npackage action_tutorials_interfaces.action;\n"));
+    assert!(proto.contains(
+        "\nimport \"builtin_interfaces/msg.proto\";\nimport \"unique_identifier_msgs/msg.proto\";\n"
+    ));
+    assert!(proto.contains("\nmessage Fibonacci_Goal {\n  int32 order = 1;\n}\n"));
+    assert!(proto.contains(
+        "\nmessage Fibonacci_SendGoal_Request {\n  unique_identifier_msgs.msg.UUID goal_id = 1;\n  action_tutorials_interfaces.action.Fibonacci_Goal goal = 2;\n}\n"
+    ));
+    assert!(proto.contains(
+        "\nservice Fibonacci {\n  rpc SendGoal(Fibonacci_SendGoal_Request) returns (Fibonacci_SendGoal_Response);\n  rpc GetResult(Fibonacci_GetResult_Request) returns (Fibonacci_GetResult_Response);\n}\n"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn generate_with_options() -> Result<()> {
+    let test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/test_data");
+    let packages = get_packages_msgs(&[&test_data.join("codegen"), &test_data.join("deps")])?;
+
+    let code = generate(
+        &packages,
+        &Options {
+            crate_path: "crate::msgs".into(),
+            derives: vec!["serde::Serialize".into()],
+            features: Features::Fixed(vec!["prost".into()]),
+            docs: false,
+            distro: Distro::Foxy,
+            ext_dir: None,
+        },
+    )?;
+    assert!(code.contains("pub stamp: crate::msgs::builtin_interfaces::msg::Time,"));
+    assert!(code.contains("crate::msgs::registry()"));
+    assert!(code.contains("std::cmp::PartialEq, serde::Serialize)]"));
+    assert!(code.contains("#[cfg(all())]\nimpl _ProtoConvert for Nested {"));
+    assert!(code.contains("#[cfg(any())]\nmod mock_rosidl {"));
+    assert!(!code.contains("///"));
+    assert!(!code.contains("feature ="));
+    assert!(code.find("pub mod builtin_interfaces {") < code.find("pub mod codegen_test_msgs {"));
+    assert!(code.contains("    codegen_test_msgs::register(registry);\n"));
+
+    Ok(())
+}
+
+#[test]
+fn generate_with_ext_dir() -> Result<()> {
+    let test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/test_data");
+    let packages = get_packages_msgs(&[&test_data.join("codegen"), &test_data.join("msg")])?;
+
+    let code = generate(
+        &packages,
+        &Options {
+            ext_dir: Some(test_data.join("ext")),
+            ..Options::default()
+        },
+    )?;
+    let header = code.find("pub struct Header {").unwrap();
+    let with_stamp = code.find("    pub fn with_stamp(").unwrap();
+    assert!(header < with_stamp);
+    assert!(with_stamp < code[header..].find("mod test {").unwrap() + header);
+    assert!(code
+        .contains("impl Lookup_Request {\n    /// Creates a request of `key` without filters.\n"));
+
+    let files = render_crate(
+        "codegen_test_msgs",
+        &packages["codegen_test_msgs"],
+        &packages,
+        &CrateOptions {
+            ext_dir: Some(test_data.join("ext")),
+            ..CrateOptions::default()
+        },
+    )?;
+    assert!(files["src/srv.rs"].contains("impl Lookup_Request {"));
+    assert!(!generate(&packages, &Options::default())?.contains("with_stamp"));
+
+    // Errors other than missing files are not ignored
+    assert!(generate(
+        &packages,
+        &Options {
+            ext_dir: Some(test_data.join("ext/std_msgs/msg/Header.rs")),
+            ..Options::default()
+        },
+    )
+    .is_err());
+
+    Ok(())
+}
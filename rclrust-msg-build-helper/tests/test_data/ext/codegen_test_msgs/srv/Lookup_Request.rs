@@ -0,0 +1,9 @@
+impl Lookup_Request {
+    /// Creates a request of `key` without filters.
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            ..Self::default()
+        }
+    }
+}
@@ -0,0 +1,25 @@
+impl Header {
+    /// Creates a header of `frame_id` stamped with `stamp`.
+    pub fn with_stamp(
+        stamp: crate::builtin_interfaces::msg::Time,
+        frame_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            stamp,
+            frame_id: frame_id.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_ext {
+    use super::*;
+
+    #[test]
+    fn test_with_stamp() {
+        let stamp = crate::builtin_interfaces::msg::Time { sec: 1, nanosec: 2 };
+        let header = Header::with_stamp(stamp.clone(), "map");
+        assert_eq!(header.stamp, stamp);
+        assert_eq!(header.frame_id, "map");
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use clap::ArgEnum;
+use rclrust_msg_build_helper::idl::render_idl;
+use rclrust_msg_build_helper::json_schema::render_json_schemas;
+use rclrust_msg_build_helper::parse::{
+    get_package_msgs_from_dir, get_packages_msgs, to_msg_literal, RosPackageMsgsMap,
+};
+use rclrust_msg_build_helper::proto::render_proto;
+use rclrust_msg_build_helper::render::{write_crates, CrateOptions};
+use rclrust_msg_build_helper::typescript::{definition_path, render_typescript};
+use rclrust_msg_core::yaml::{to_yaml, YamlOptions};
+use rclrust_msg_core::{DynamicMessage, TypeResolver};
+use rclrust_msg_types::{Action, Member, Message, NestableType, Service};
+
+const ROSIDL_INTERFACES: &str = "share/ament_index/resource_index/rosidl_interfaces";
+
+/// An interface found by its type name
+#[derive(Debug, Clone, Copy)]
+pub enum Interface<'a> {
+    Message(&'a Message),
+    Service(&'a Service),
+    Action(&'a Action),
+}
+
+/// Schema formats which interfaces can be exported to
+#[derive(Debug, Clone, Copy, ArgEnum)]
+pub enum Format {
+    /// Protocol Buffers (proto3)
+    Protobuf,
+    /// JSON Schema (draft 2020-12)
+    JsonSchema,
+    /// OMG IDL as generated by rosidl_adapter
+    Idl,
+    /// TypeScript type definitions
+    Typescript,
+}
+
+/// Interfaces installed in ament prefixes
+#[derive(Debug)]
+pub struct Interfaces {
+    packages: RosPackageMsgsMap,
+}
+
+impl Interfaces {
+    pub fn load(paths: &[&Path]) -> Result<Self> {
+        Ok(Self {
+            packages: get_packages_msgs(paths)?,
+        })
+    }
+
+    /// Loads the interfaces in an ament prefix or a package directory.
+    pub fn load_dir(path: &Path) -> Result<Self> {
+        if path.join(ROSIDL_INTERFACES).is_dir() {
+            return Self::load(&[path]);
+        }
+
+        let package = fs::read_to_string(path.join("package.xml"))
+            .ok()
+            .and_then(|xml| {
+                let start = xml.find("<name>")? + "<name>".len();
+                let end = start + xml[start..].find("</name>")?;
+                Some(xml[start..end].trim().to_string())
+            })
+            .or_else(|| Some(path.canonicalize().ok()?.file_name()?.to_str()?.to_string()))
+            .ok_or_else(|| anyhow!("Unknown package name: {}", path.display()))?;
+        let mut packages = RosPackageMsgsMap::new();
+        let msgs = get_package_msgs_from_dir(&package, path)?;
+        packages.insert(package, msgs);
+        Ok(Self { packages })
+    }
+
+    pub fn packages(&self) -> impl Iterator<Item = &str> {
+        let mut packages = self.packages.keys().map(String::as_str).collect::<Vec<_>>();
+        packages.sort_unstable();
+        packages.into_iter()
+    }
+
+    /// Returns the full names of the messages, services and actions in `package`.
+    pub fn package(&self, package: &str) -> Result<(Vec<String>, Vec<String>, Vec<String>)> {
+        let msgs = self
+            .packages
+            .get(package)
+            .ok_or_else(|| anyhow!("Unknown package: {}", package))?;
+        let names = |namespace: &str, names: Vec<&String>| {
+            let mut names = names
+                .into_iter()
+                .map(|name| format!("{}/{}/{}", package, namespace, name))
+                .collect::<Vec<_>>();
+            names.sort();
+            names
+        };
+        Ok((
+            names("msg", msgs.msgs.iter().map(|v| &v.name).collect()),
+            names("srv", msgs.srvs.iter().map(|v| &v.name).collect()),
+            names("action", msgs.actions.iter().map(|v| &v.name).collect()),
+        ))
+    }
+
+    /// Finds an interface by `<package>/<namespace>/<name>` or `<package>/<name>`.
+    pub fn find(&self, type_name: &str) -> Result<Interface<'_>> {
+        let unknown = || anyhow!("Unknown type: {}", type_name);
+        let (package, namespace, name) = match type_name.split('/').collect::<Vec<_>>()[..] {
+            [package, name] => (package, None, name),
+            [package, namespace, name] => (package, Some(namespace), name),
+            _ => bail!("Invalid type name: {}", type_name),
+        };
+        let msgs = self.packages.get(package).ok_or_else(unknown)?;
+
+        let msg = msgs.msgs.iter().find(|v| v.name == name);
+        let srv = msgs.srvs.iter().find(|v| v.name == name);
+        let action = msgs.actions.iter().find(|v| v.name == name);
+        match (namespace, msg, srv, action) {
+            (Some("msg") | None, Some(v), _, _) => Ok(Interface::Message(v)),
+            (Some("srv") | None, _, Some(v), _) => Ok(Interface::Service(v)),
+            (Some("action") | None, _, _, Some(v)) => Ok(Interface::Action(v)),
+            _ => Err(unknown()),
+        }
+    }
+
+    /// Returns the definition of `type_name` with the nested types expanded.
+    pub fn show(&self, type_name: &str) -> Result<String> {
+        let mut out = String::new();
+        match self.find(type_name)? {
+            Interface::Message(msg) => self.write_message(&mut out, msg, 0),
+            Interface::Service(srv) => {
+                self.write_message(&mut out, &srv.request, 0);
+                out.push_str("---\n");
+                self.write_message(&mut out, &srv.response, 0);
+            }
+            Interface::Action(action) => {
+                self.write_message(&mut out, &action.goal, 0);
+                out.push_str("---\n");
+                self.write_message(&mut out, &action.result, 0);
+                out.push_str("---\n");
+                self.write_message(&mut out, &action.feedback, 0);
+            }
+        }
+        Ok(out)
+    }
+
+    fn write_message(&self, out: &mut String, msg: &Message, depth: usize) {
+        let indent = "\t".repeat(depth);
+        for constant in &msg.constants {
+            writeln!(
+                out,
+                "{}{} {}={}",
+                indent,
+                constant.r#type,
+                constant.name,
+                to_msg_literal(&constant.value)
+            )
+            .unwrap();
+        }
+        for member in &msg.members {
+            self.write_member(out, &msg.package, member, depth);
+        }
+    }
+
+    fn write_member(&self, out: &mut String, package: &str, member: &Member, depth: usize) {
+        write!(
+            out,
+            "{}{} {}",
+            "\t".repeat(depth),
+            member.r#type,
+            member.name
+        )
+        .unwrap();
+        if let Some(default) = &member.default {
+            write!(out, " {}", to_msg_literal(default)).unwrap();
+        }
+        out.push('\n');
+
+        let nested = match member.r#type.clone().inner_type() {
+            NestableType::NamedType(t) => self.message(package, "msg", &t.0),
+            NestableType::NamespacedType(t) => self.message(&t.package, &t.namespace, &t.name),
+            _ => None,
+        };
+        if let Some(nested) = nested {
+            self.write_message(out, nested, depth + 1);
+        }
+    }
+
+    fn message(&self, package: &str, namespace: &str, name: &str) -> Option<&Message> {
+        let msgs = self.packages.get(package)?;
+        match namespace {
+            "msg" => msgs.msgs.iter().find(|v| v.name == name),
+            // Messages of actions, e.g. `Fibonacci_Goal`
+            "action" => msgs.actions.iter().find_map(|action| {
+                [&action.goal, &action.result, &action.feedback]
+                    .iter()
+                    .copied()
+                    .find(|v| v.name == name)
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns the default value of the message `type_name` as YAML.
+    pub fn proto(&self, type_name: &str) -> Result<String> {
+        let msg = match self.find(type_name)? {
+            Interface::Message(msg) => msg,
+            _ => bail!("{} is not a message", type_name),
+        };
+
+        let mut resolver = TypeResolver::new();
+        for msgs in self.packages.values() {
+            msgs.msgs
+                .iter()
+                .cloned()
+                .for_each(|v| resolver.add_message(v));
+            msgs.actions
+                .iter()
+                .cloned()
+                .for_each(|v| resolver.add_action(v));
+        }
+        let description = resolver.resolve(&format!("{}/msg/{}", msg.package, msg.name))?;
+        Ok(to_yaml(
+            &DynamicMessage::new(description)?,
+            &YamlOptions::default(),
+        ))
+    }
+
+    /// Returns all the messages including the ones of services and actions.
+    pub fn messages(&self) -> Vec<Message> {
+        let mut messages = Vec::new();
+        for msgs in self.packages.values() {
+            messages.extend(msgs.msgs.iter().cloned());
+            for srv in &msgs.srvs {
+                messages.extend([srv.request.clone(), srv.response.clone()]);
+            }
+            for action in &msgs.actions {
+                messages.extend([
+                    action.goal.clone(),
+                    action.result.clone(),
+                    action.feedback.clone(),
+                ]);
+            }
+        }
+        messages
+    }
+
+    /// Writes a crate for each of `packages` and their dependencies into `out_dir`.
+    pub fn generate(
+        &self,
+        packages: &[&str],
+        out_dir: &Path,
+        options: &CrateOptions,
+    ) -> Result<Vec<String>> {
+        write_crates(&self.packages, packages, out_dir, options)
+    }
+
+    /// Writes a schema in `format` for each of `packages` into `out_dir`.
+    ///
+    /// All the packages are exported if `packages` is empty. Returns the paths of the written files.
+    pub fn export(
+        &self,
+        format: Format,
+        packages: &[&str],
+        out_dir: &Path,
+    ) -> Result<Vec<PathBuf>> {
+        let packages = if packages.is_empty() {
+            self.packages().collect()
+        } else {
+            packages.to_vec()
+        };
+        let mut paths = Vec::new();
+        for package in packages {
+            let msgs = self
+                .packages
+                .get(package)
+                .ok_or_else(|| anyhow!("Unknown package: {}", package))?;
+            let files = match format {
+                Format::Protobuf => render_proto(package, msgs),
+                Format::JsonSchema => render_json_schemas(package, msgs),
+                Format::Idl => render_idl(package, msgs),
+                Format::Typescript => {
+                    BTreeMap::from([(definition_path(package), render_typescript(package, msgs))])
+                }
+            };
+            for (path, contents) in files {
+                let path = out_dir.join(path);
+                fs::create_dir_all(path.parent().unwrap())?;
+                fs::write(&path, contents)?;
+                paths.push(path);
+            }
+        }
+        Ok(paths)
+    }
+}
@@ -0,0 +1,189 @@
+#![warn(
+    rust_2018_idioms,
+    elided_lifetimes_in_paths,
+    clippy::all,
+    clippy::nursery
+)]
+
+mod interfaces;
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use clap::{Parser, Subcommand};
+use rclrust_msg_build_helper::parse::to_msg_literal;
+use rclrust_msg_build_helper::render::{CrateOptions, Distro};
+use rclrust_msg_types::diff::{diff_messages, MessageDiff};
+
+use crate::interfaces::{Format, Interfaces};
+
+/// Inspects ROS 2 interfaces without a running ROS system
+#[derive(Debug, Parser)]
+#[clap(name = "rclrust-msg")]
+struct Cli {
+    /// Ament prefixes to search interfaces in (default: $AMENT_PREFIX_PATH)
+    #[clap(short, long = "path", global = true)]
+    paths: Vec<PathBuf>,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// List all interface types
+    List,
+    /// List all packages with interfaces
+    Packages,
+    /// List the interface types of a package
+    Package { package: String },
+    /// Show the definition of an interface type
+    Show { type_name: String },
+    /// Print a YAML prototype of a message type
+    Proto { type_name: String },
+    /// Compare interfaces and fail if some changes break the wire compatibility
+    Diff {
+        /// Old interfaces in an ament prefix or a package directory
+        old: PathBuf,
+        /// New interfaces in an ament prefix or a package directory
+        new: PathBuf,
+    },
+    /// Generate a crate for each package and the packages it depends on
+    Generate {
+        /// Directory to write the crates into
+        #[clap(short, long)]
+        out_dir: PathBuf,
+        /// Version of the generated crates
+        #[clap(long, default_value = "0.1.0")]
+        version: String,
+        /// Dependency of rclrust-msg-core in Cargo.toml, e.g. '{ path = "../core" }'
+        #[clap(long)]
+        core_dependency: Option<String>,
+        /// ROS 2 distro of the interfaces, e.g. humble (default: $ROS_DISTRO, or foxy)
+        #[clap(long, value_parser = parse_distro)]
+        distro: Option<Distro>,
+        /// Directory of hand-written code to add to the types, in <package>/<namespace>/<Type>.rs
+        #[clap(long)]
+        ext_dir: Option<PathBuf>,
+        /// Packages to generate (default: all)
+        packages: Vec<String>,
+    },
+    /// Export a schema of each package in another interface description language
+    Export {
+        /// Format of the schemas
+        #[clap(short, long, arg_enum)]
+        format: Format,
+        /// Directory to write the schemas into
+        #[clap(short, long)]
+        out_dir: PathBuf,
+        /// Packages to export (default: all)
+        packages: Vec<String>,
+    },
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    if let Command::Diff { old, new } = &cli.command {
+        return diff(old, new);
+    }
+
+    let paths = if cli.paths.is_empty() {
+        match std::env::var("AMENT_PREFIX_PATH") {
+            Ok(paths) => paths.split(':').map(PathBuf::from).collect(),
+            Err(_) => bail!("$AMENT_PREFIX_PATH is not set and no --path is given"),
+        }
+    } else {
+        cli.paths
+    };
+    let interfaces = Interfaces::load(&paths.iter().map(PathBuf::as_path).collect::<Vec<&Path>>())?;
+
+    match cli.command {
+        Command::List => {
+            let (mut msgs, mut srvs, mut actions) = (Vec::new(), Vec::new(), Vec::new());
+            for package in interfaces.packages() {
+                let (m, s, a) = interfaces.package(package)?;
+                msgs.extend(m);
+                srvs.extend(s);
+                actions.extend(a);
+            }
+            for (title, names) in [("Messages", msgs), ("Services", srvs), ("Actions", actions)] {
+                println!("{}:", title);
+                for name in names {
+                    println!("    {}", name);
+                }
+            }
+        }
+        Command::Packages => interfaces
+            .packages()
+            .for_each(|package| println!("{}", package)),
+        Command::Package { package } => {
+            let (msgs, srvs, actions) = interfaces.package(&package)?;
+            for name in msgs.iter().chain(&srvs).chain(&actions) {
+                println!("{}", name);
+            }
+        }
+        Command::Show { type_name } => print!("{}", interfaces.show(&type_name)?),
+        Command::Proto { type_name } => println!("\"{}\"", interfaces.proto(&type_name)?),
+        Command::Diff { .. } => unreachable!(),
+        Command::Generate {
+            out_dir,
+            version,
+            core_dependency,
+            distro,
+            ext_dir,
+            packages,
+        } => {
+            let mut options = CrateOptions {
+                version,
+                distro: distro.or_else(Distro::from_env).unwrap_or(Distro::Foxy),
+                ext_dir,
+                ..CrateOptions::default()
+            };
+            if let Some(core_dependency) = core_dependency {
+                options.core_dependency = core_dependency;
+            }
+            let packages = packages.iter().map(String::as_str).collect::<Vec<_>>();
+            for package in interfaces.generate(&packages, &out_dir, &options)? {
+                println!("{}", out_dir.join(package).display());
+            }
+        }
+        Command::Export {
+            format,
+            out_dir,
+            packages,
+        } => {
+            let packages = packages.iter().map(String::as_str).collect::<Vec<_>>();
+            for path in interfaces.export(format, &packages, &out_dir)? {
+                println!("{}", path.display());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_distro(s: &str) -> Result<Distro> {
+    Distro::parse(s).ok_or_else(|| anyhow!("Unknown distro: {}", s))
+}
+
+fn diff(old: &Path, new: &Path) -> Result<()> {
+    let old = Interfaces::load_dir(old)?.messages();
+    let new = Interfaces::load_dir(new)?.messages();
+
+    let diffs = diff_messages(&old, &new);
+    for diff in &diffs {
+        println!("{}/{}", diff.package, diff.name);
+        for change in &diff.changes {
+            let kind = if change.is_wire_breaking() {
+                "breaking"
+            } else {
+                "compatible"
+            };
+            println!("    {}: {}", kind, to_msg_literal(&change.to_string()));
+        }
+    }
+    if diffs.iter().any(MessageDiff::is_wire_breaking) {
+        bail!("Found wire-breaking changes");
+    }
+    Ok(())
+}
@@ -0,0 +1,457 @@
+use std::process::Command;
+
+use anyhow::Result;
+
+fn run(args: &[&str]) -> Result<String> {
+    let output = Command::new(env!("CARGO_BIN_EXE_rclrust-msg"))
+        .args([
+            "--path",
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/ament"),
+        ])
+        .args(args)
+        .env_remove("AMENT_PREFIX_PATH")
+        .env_remove("ROS_DISTRO")
+        .output()?;
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+#[test]
+fn list() -> Result<()> {
+    assert_eq!(
+        run(&["list"])?,
+        "\
+Messages:
+    builtin_interfaces/msg/Time
+    example_interfaces/msg/Defaults
+    std_msgs/msg/Header
+    unique_identifier_msgs/msg/UUID
+Services:
+    example_interfaces/srv/AddTwoInts
+Actions:
+    example_interfaces/action/Fibonacci
+"
+    );
+    Ok(())
+}
+
+#[test]
+fn packages() -> Result<()> {
+    assert_eq!(
+        run(&["packages"])?,
+        "builtin_interfaces\nexample_interfaces\nstd_msgs\nunique_identifier_msgs\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn package() -> Result<()> {
+    assert_eq!(
+        run(&["package", "example_interfaces"])?,
+        "\
+example_interfaces/msg/Defaults
+example_interfaces/srv/AddTwoInts
+example_interfaces/action/Fibonacci
+"
+    );
+    Ok(())
+}
+
+#[test]
+fn show() -> Result<()> {
+    assert_eq!(
+        run(&["show", "example_interfaces/msg/Defaults"])?,
+        "\
+int32 ANSWER=42
+string GREETING=\"hello\"
+std_msgs/Header header
+\tbuiltin_interfaces/Time stamp
+\t\tint32 sec
+\t\tuint32 nanosec
+\tstring frame_id
+string<=8 name \"robot\"
+float64[] data [1.5, 2.0]
+bool[2] flags
+"
+    );
+    assert_eq!(
+        run(&["show", "example_interfaces/AddTwoInts"])?,
+        "int64 a\nint64 b\n---\nint64 sum\n"
+    );
+    assert_eq!(
+        run(&["show", "example_interfaces/action/Fibonacci"])?,
+        "int32 order\n---\nint32[] sequence\n---\nint32[] partial_sequence\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn proto() -> Result<()> {
+    assert_eq!(
+        run(&["proto", "example_interfaces/msg/Defaults"])?,
+        "\
+\"header:
+  stamp:
+    sec: 0
+    nanosec: 0
+  frame_id: ''
+name: robot
+data: [1.5, 2.0]
+flags: [false, false]
+\"
+"
+    );
+    Ok(())
+}
+
+#[test]
+fn generate() -> Result<()> {
+    let out_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("generate");
+    let _ = std::fs::remove_dir_all(&out_dir);
+    let out_dir_str = out_dir.to_str().unwrap();
+
+    assert_eq!(
+        run(&["generate", "--out-dir", out_dir_str, "example_interfaces"])?,
+        format!(
+            "{0}/builtin_interfaces\n{0}/example_interfaces\n{0}/std_msgs\n{0}/unique_identifier_msgs\n",
+            out_dir_str
+        )
+    );
+
+    let cargo_toml = std::fs::read_to_string(out_dir.join("example_interfaces/Cargo.toml"))?;
+    assert!(cargo_toml.contains("name = \"example_interfaces\"\n"));
+    assert!(cargo_toml.contains("builtin_interfaces = { path = \"../builtin_interfaces\" }\n"));
+    assert!(cargo_toml.contains("std_msgs = { path = \"../std_msgs\" }\n"));
+    assert!(
+        cargo_toml.contains("unique_identifier_msgs = { path = \"../unique_identifier_msgs\" }\n")
+    );
+
+    let msg_rs = std::fs::read_to_string(out_dir.join("example_interfaces/src/msg.rs"))?;
+    assert!(msg_rs.contains("pub header: ::std_msgs::msg::Header,"));
+    for path in ["build.rs", "src/lib.rs", "src/srv.rs", "src/action.rs"] {
+        assert!(out_dir.join("example_interfaces").join(path).exists());
+    }
+    assert!(!out_dir.join("builtin_interfaces/src/srv.rs").exists());
+    Ok(())
+}
+
+#[test]
+fn generate_distro() {
+    let out_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("generate_distro");
+    let generate = |distro: &str| {
+        Command::new(env!("CARGO_BIN_EXE_rclrust-msg"))
+            .args([
+                "--path",
+                concat!(env!("CARGO_MANIFEST_DIR"), "/tests/ament"),
+            ])
+            .args(["generate", "--out-dir", out_dir.to_str().unwrap()])
+            .args(["--distro", distro, "example_interfaces"])
+            .output()
+            .unwrap()
+    };
+
+    assert!(generate("humble").status.success());
+    let output = generate("jazzy");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("example_interfaces depends on unknown package: service_msgs"));
+    assert!(!generate("ardent").status.success());
+}
+
+#[test]
+fn export() -> Result<()> {
+    let out_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("export");
+    let _ = std::fs::remove_dir_all(&out_dir);
+    let out_dir_str = out_dir.to_str().unwrap();
+
+    assert_eq!(
+        run(&["export", "--format", "protobuf", "--out-dir", out_dir_str, "example_interfaces"])?,
+        format!(
+            "{0}/example_interfaces/action.proto\n{0}/example_interfaces/msg.proto\n{0}/example_interfaces/srv.proto\n",
+            out_dir_str
+        )
+    );
+    assert_eq!(
+        std::fs::read_to_string(out_dir.join("example_interfaces/msg.proto"))?,
+        "\
+// Generated from the ROS interfaces in example_interfaces/msg
+syntax = \"proto3\";
+
+package example_interfaces.msg;
+
+import \"std_msgs/msg.proto\";
+
+// example_interfaces/msg/Defaults
+message Defaults {
+  // int32 ANSWER = 42
+  // string GREETING = \"hello\"
+  std_msgs.msg.Header header = 1;
+  string name = 2;  // default: \"robot\"
+  repeated double data = 3;  // default: [1.5, 2.0]
+  repeated bool flags = 4;
+}
+"
+    );
+    assert!(
+        std::fs::read_to_string(out_dir.join("example_interfaces/srv.proto"))?.contains(
+            "service AddTwoInts {\n  rpc Call(AddTwoInts_Request) returns (AddTwoInts_Response);\n}\n"
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn export_json_schema() -> Result<()> {
+    let out_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("export_json_schema");
+    let _ = std::fs::remove_dir_all(&out_dir);
+    let out_dir_str = out_dir.to_str().unwrap();
+
+    let output = run(&[
+        "export",
+        "-f",
+        "json-schema",
+        "-o",
+        out_dir_str,
+        "example_interfaces",
+    ])?;
+    assert_eq!(
+        output
+            .lines()
+            .map(|line| &line[out_dir_str.len() + 1..])
+            .collect::<Vec<_>>(),
+        [
+            "example_interfaces/action/Fibonacci_Feedback.json",
+            "example_interfaces/action/Fibonacci_Goal.json",
+            "example_interfaces/action/Fibonacci_Result.json",
+            "example_interfaces/msg/Defaults.json",
+            "example_interfaces/srv/AddTwoInts_Request.json",
+            "example_interfaces/srv/AddTwoInts_Response.json",
+        ]
+    );
+    assert_eq!(
+        std::fs::read_to_string(out_dir.join("example_interfaces/msg/Defaults.json"))?,
+        r#"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "title": "example_interfaces/msg/Defaults",
+  "type": "object",
+  "properties": {
+    "header": {
+      "$ref": "../../std_msgs/msg/Header.json"
+    },
+    "name": {
+      "type": "string",
+      "maxLength": 8,
+      "default": "robot"
+    },
+    "data": {
+      "type": "array",
+      "items": {
+        "type": "number"
+      },
+      "default": [
+        1.5,
+        2.0
+      ]
+    },
+    "flags": {
+      "type": "array",
+      "items": {
+        "type": "boolean"
+      },
+      "minItems": 2,
+      "maxItems": 2
+    }
+  },
+  "additionalProperties": false
+}
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn export_idl() -> Result<()> {
+    let out_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("export_idl");
+    let _ = std::fs::remove_dir_all(&out_dir);
+    let out_dir_str = out_dir.to_str().unwrap();
+
+    let output = run(&[
+        "export",
+        "-f",
+        "idl",
+        "-o",
+        out_dir_str,
+        "example_interfaces",
+    ])?;
+    assert_eq!(
+        output
+            .lines()
+            .map(|line| &line[out_dir_str.len() + 1..])
+            .collect::<Vec<_>>(),
+        [
+            "example_interfaces/action/Fibonacci.idl",
+            "example_interfaces/msg/Defaults.idl",
+            "example_interfaces/srv/AddTwoInts.idl",
+        ]
+    );
+    assert_eq!(
+        std::fs::read_to_string(out_dir.join("example_interfaces/msg/Defaults.idl"))?,
+        r#"// generated by rclrust-msg
+// with input from example_interfaces/msg/Defaults.msg
+
+#include "std_msgs/msg/Header.idl"
+
+module example_interfaces {
+  module msg {
+    module Defaults_Constants {
+      const int32 ANSWER = 42;
+      const string GREETING = "hello";
+    };
+    typedef boolean boolean__2[2];
+    struct Defaults {
+      std_msgs::msg::Header header;
+
+      @default (value="robot")
+      string<8> name;
+
+      @default (value="(1.5, 2.0)")
+      sequence<double> data;
+
+      boolean__2 flags;
+    };
+  };
+};
+"#
+    );
+    assert_eq!(
+        std::fs::read_to_string(out_dir.join("example_interfaces/srv/AddTwoInts.idl"))?,
+        r#"// generated by rclrust-msg
+// with input from example_interfaces/srv/AddTwoInts.srv
+
+module example_interfaces {
+  module srv {
+    struct AddTwoInts_Request {
+      int64 a;
+
+      int64 b;
+    };
+    struct AddTwoInts_Response {
+      int64 sum;
+    };
+  };
+};
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn export_typescript() -> Result<()> {
+    let out_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("export_typescript");
+    let _ = std::fs::remove_dir_all(&out_dir);
+    let out_dir_str = out_dir.to_str().unwrap();
+
+    assert_eq!(
+        run(&[
+            "export",
+            "-f",
+            "typescript",
+            "-o",
+            out_dir_str,
+            "example_interfaces"
+        ])?,
+        format!("{}/example_interfaces.d.ts\n", out_dir_str)
+    );
+    assert_eq!(
+        std::fs::read_to_string(out_dir.join("example_interfaces.d.ts"))?,
+        r#"// Generated from the ROS interfaces in example_interfaces
+
+import * as std_msgs from "./std_msgs";
+
+export namespace msg {
+  export interface Defaults {
+    header: std_msgs.msg.Header;
+    name: string;
+    data: number[];
+    flags: boolean[];
+  }
+
+  export namespace Defaults {
+    export const ANSWER = 42;
+    export const GREETING = "hello";
+  }
+}
+
+export namespace srv {
+  export interface AddTwoInts_Request {
+    a: number;
+    b: number;
+  }
+
+  export interface AddTwoInts_Response {
+    sum: number;
+  }
+}
+
+export namespace action {
+  export interface Fibonacci_Goal {
+    order: number;
+  }
+
+  export interface Fibonacci_Result {
+    sequence: number[];
+  }
+
+  export interface Fibonacci_Feedback {
+    partial_sequence: number[];
+  }
+}
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn diff() -> Result<()> {
+    let dir = env!("CARGO_MANIFEST_DIR");
+    let old = format!("{}/tests/ament/share/example_interfaces", dir);
+    let new = format!("{}/tests/diff/example_interfaces", dir);
+
+    assert_eq!(run(&["diff", &old, &old])?, "");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rclrust-msg"))
+        .args(["diff", &old, &new])
+        .output()?;
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        "\
+example_interfaces/AddTwoInts_Request
+    compatible: field `b` renamed to `y`
+example_interfaces/Defaults
+    compatible: default of `name` changed from \"robot\" to \"bot\"
+    breaking: bound of `data` changed from float64[] to float64[<=4]
+    compatible: constant `ANSWER` changed from `int32 42` to `int32 43`
+example_interfaces/Fibonacci_Result
+    breaking: type of `sequence` changed from int32[] to int64[]
+"
+    );
+    Ok(())
+}
+
+#[test]
+fn unknown_type() {
+    let output = Command::new(env!("CARGO_BIN_EXE_rclrust-msg"))
+        .args([
+            "--path",
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/ament"),
+        ])
+        .args(["show", "std_msgs/msg/Bool"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
@@ -0,0 +1,23 @@
+use std::env::VarError;
+
+use rclrust_msg_types::Distro;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=ROS_DISTRO");
+    println!("cargo:rustc-check-cfg=cfg(rosidl_member_accessors)");
+
+    let distro = match std::env::var("ROS_DISTRO") {
+        Ok(name) => Distro::parse(&name)
+            .unwrap_or_else(|| panic!("$ROS_DISTRO `{}` is not a supported distro.", name)),
+        Err(VarError::NotPresent) => {
+            println!("cargo:warning=$ROS_DISTRO is not set, so the layout of Foxy is used.");
+            Distro::Foxy
+        }
+        Err(e) => panic!("$ROS_DISTRO is invalid: {}", e),
+    };
+
+    // The members tables of `rosidl_typesupport_introspection_c` depend on the distro
+    if distro.has_member_accessors() {
+        println!("cargo:rustc-cfg=rosidl_member_accessors");
+    }
+}
@@ -0,0 +1,83 @@
+//! Helpers of the messages of the action protocol
+
+use std::fmt;
+
+use uuid::Uuid;
+
+/// The identifier of a goal, i.e. the content of `unique_identifier_msgs/msg/UUID`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GoalId([u8; 16]);
+
+impl GoalId {
+    /// Generates a random (version 4) UUID.
+    pub fn random() -> Self {
+        Self(*Uuid::new_v4().as_bytes())
+    }
+
+    pub const fn from_bytes(bytes: [u8; 16]) -> Self {
+        Self(bytes)
+    }
+
+    pub const fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+
+    pub const fn into_bytes(self) -> [u8; 16] {
+        self.0
+    }
+}
+
+impl From<[u8; 16]> for GoalId {
+    fn from(bytes: [u8; 16]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<GoalId> for [u8; 16] {
+    fn from(id: GoalId) -> Self {
+        id.0
+    }
+}
+
+impl From<Uuid> for GoalId {
+    fn from(uuid: Uuid) -> Self {
+        Self(uuid.into_bytes())
+    }
+}
+
+impl From<GoalId> for Uuid {
+    fn from(id: GoalId) -> Self {
+        Self::from_bytes(id.0)
+    }
+}
+
+/// Formats the identifier as a hyphenated UUID, e.g. `67e55044-10b1-426f-9247-bb680e5fe0c8`
+impl fmt::Display for GoalId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Uuid::from_bytes(self.0).hyphenated().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn random() {
+        let (a, b) = (GoalId::random(), GoalId::random());
+        assert_ne!(a, b);
+        assert_eq!(Uuid::from(a).get_version_num(), 4);
+    }
+
+    #[test]
+    fn bytes() {
+        let bytes = [
+            0x67, 0xe5, 0x50, 0x44, 0x10, 0xb1, 0x42, 0x6f, 0x92, 0x47, 0xbb, 0x68, 0x0e, 0x5f,
+            0xe0, 0xc8,
+        ];
+        let id = GoalId::from(bytes);
+        assert_eq!(<[u8; 16]>::from(id), bytes);
+        assert_eq!(id.as_bytes(), &bytes);
+        assert_eq!(id.to_string(), "67e55044-10b1-426f-9247-bb680e5fe0c8");
+    }
+}
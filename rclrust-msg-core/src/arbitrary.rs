@@ -0,0 +1,185 @@
+//! Generation of arbitrary values of the fields of messages for property based testing
+//!
+//! The values respect the sizes of arrays and the bounds of strings and sequences. Floating point
+//! values are never NaN and strings never contain null characters, so that generated messages
+//! compare equal to themselves after a round trip through the rosidl C representation.
+
+/// The maximum length of unbounded strings and sequences
+pub const MAX_UNBOUNDED_LEN: usize = 16;
+
+/// Strategies of `proptest`
+#[cfg(feature = "proptest")]
+pub mod proptest {
+    use ::proptest::collection::vec;
+    use ::proptest::num::{f32 as f32_, f64 as f64_};
+    use ::proptest::prelude::*;
+    use widestring::U16String;
+
+    use super::MAX_UNBOUNDED_LEN;
+
+    /// Any `f32` but NaN
+    pub fn f32() -> impl Strategy<Value = f32> {
+        f32_::POSITIVE
+            | f32_::NEGATIVE
+            | f32_::NORMAL
+            | f32_::SUBNORMAL
+            | f32_::ZERO
+            | f32_::INFINITE
+    }
+
+    /// Any `f64` but NaN
+    pub fn f64() -> impl Strategy<Value = f64> {
+        f64_::POSITIVE
+            | f64_::NEGATIVE
+            | f64_::NORMAL
+            | f64_::SUBNORMAL
+            | f64_::ZERO
+            | f64_::INFINITE
+    }
+
+    /// Strings of at most `bound` bytes without null characters
+    pub fn string(bound: Option<usize>) -> impl Strategy<Value = String> {
+        let max_len = bound.unwrap_or(MAX_UNBOUNDED_LEN);
+        vec(
+            any::<char>().prop_filter("null", |&c| c != '\0'),
+            0..=max_len,
+        )
+        .prop_map(move |chars| {
+            let mut s = String::new();
+            for c in chars {
+                if s.len() + c.len_utf8() > max_len {
+                    break;
+                }
+                s.push(c);
+            }
+            s
+        })
+    }
+
+    /// Wide strings of at most `bound` code units without null characters
+    pub fn wstring(bound: Option<usize>) -> impl Strategy<Value = U16String> {
+        vec(1..=u16::MAX, 0..=bound.unwrap_or(MAX_UNBOUNDED_LEN)).prop_map(U16String::from_vec)
+    }
+
+    /// Sequences of at most `max_size` elements
+    pub fn sequence<S: Strategy>(
+        element: S,
+        max_size: Option<usize>,
+    ) -> impl Strategy<Value = Vec<S::Value>> {
+        vec(element, 0..=max_size.unwrap_or(MAX_UNBOUNDED_LEN))
+    }
+
+    /// Arrays of `N` elements
+    pub fn array<S: Strategy, const N: usize>(element: S) -> impl Strategy<Value = [S::Value; N]> {
+        ::proptest::array::uniform(element)
+    }
+}
+
+/// Generators of `quickcheck`
+#[cfg(feature = "quickcheck")]
+pub mod quickcheck {
+    use ::quickcheck::{Arbitrary, Gen};
+    use widestring::U16String;
+
+    use super::MAX_UNBOUNDED_LEN;
+
+    fn len(g: &mut Gen, max_len: Option<usize>) -> usize {
+        usize::arbitrary(g) % (max_len.unwrap_or(MAX_UNBOUNDED_LEN) + 1)
+    }
+
+    /// Any `f32` but NaN
+    pub fn f32(g: &mut Gen) -> f32 {
+        loop {
+            let v = f32::arbitrary(g);
+            if !v.is_nan() {
+                return v;
+            }
+        }
+    }
+
+    /// Any `f64` but NaN
+    pub fn f64(g: &mut Gen) -> f64 {
+        loop {
+            let v = f64::arbitrary(g);
+            if !v.is_nan() {
+                return v;
+            }
+        }
+    }
+
+    /// A string of at most `bound` bytes without null characters
+    pub fn string(g: &mut Gen, bound: Option<usize>) -> String {
+        let max_len = len(g, bound);
+        let mut s = String::new();
+        while s.len() < max_len {
+            let c = char::arbitrary(g);
+            if c == '\0' {
+                continue;
+            }
+            if s.len() + c.len_utf8() > max_len {
+                break;
+            }
+            s.push(c);
+        }
+        s
+    }
+
+    /// A wide string of at most `bound` code units without null characters
+    pub fn wstring(g: &mut Gen, bound: Option<usize>) -> U16String {
+        let len = len(g, bound);
+        U16String::from_vec(
+            std::iter::repeat_with(|| u16::arbitrary(g).max(1))
+                .take(len)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// A sequence of at most `max_size` elements
+    pub fn sequence<T>(
+        g: &mut Gen,
+        max_size: Option<usize>,
+        mut element: impl FnMut(&mut Gen) -> T,
+    ) -> Vec<T> {
+        let len = len(g, max_size);
+        (0..len).map(|_| element(g)).collect()
+    }
+
+    /// An array of `N` elements
+    pub fn array<T, const N: usize>(g: &mut Gen, mut element: impl FnMut(&mut Gen) -> T) -> [T; N] {
+        std::array::from_fn(|_| element(g))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "proptest")]
+    ::proptest::proptest! {
+        #[test]
+        fn proptest_bounds(
+            s in super::proptest::string(Some(5)),
+            w in super::proptest::wstring(Some(3)),
+            v in super::proptest::sequence(super::proptest::f64(), Some(2)),
+        ) {
+            ::proptest::prop_assert!(s.len() <= 5 && !s.contains('\0'));
+            ::proptest::prop_assert!(w.len() <= 3 && !w.as_slice().contains(&0));
+            ::proptest::prop_assert!(v.len() <= 2 && v.iter().all(|v| !v.is_nan()));
+        }
+    }
+
+    #[cfg(feature = "quickcheck")]
+    #[test]
+    fn quickcheck_bounds() {
+        use super::quickcheck::*;
+
+        let mut g = ::quickcheck::Gen::new(100);
+        for _ in 0..100 {
+            let s = string(&mut g, Some(5));
+            assert!(s.len() <= 5 && !s.contains('\0'));
+            let w = wstring(&mut g, Some(3));
+            assert!(w.len() <= 3 && !w.as_slice().contains(&0));
+            let v: [f32; 3] = array(&mut g, f32);
+            assert!(v.iter().all(|v| !v.is_nan()));
+            assert!(sequence(&mut g, Some(2), f64).len() <= 2);
+        }
+    }
+}
@@ -0,0 +1,327 @@
+//! Encoding of [`DynamicMessage`]s in the CDR format used by the DDS based rmw implementations
+
+use std::convert::TryInto;
+
+use widestring::U16String;
+
+use crate::dynamic::{DynamicMessage, Value};
+use crate::error::DynamicError;
+use crate::introspection::{BasicType, FieldType, MessageDescription, ValueType};
+
+/// Encapsulation header of little endian plain CDR
+const CDR_LE: [u8; 4] = [0x00, 0x01, 0x00, 0x00];
+/// Encapsulation header of big endian plain CDR
+const CDR_BE: [u8; 4] = [0x00, 0x00, 0x00, 0x00];
+
+/// Encodes `msg` as little endian CDR including the encapsulation header.
+pub fn encode(msg: &DynamicMessage) -> Vec<u8> {
+    let mut writer = Writer {
+        buf: CDR_LE.to_vec(),
+    };
+    writer.write_message(msg);
+    writer.buf
+}
+
+/// Decodes CDR `data` including the encapsulation header as a message of `description`.
+pub fn decode(
+    description: &'static MessageDescription,
+    data: &[u8],
+) -> Result<DynamicMessage, DynamicError> {
+    let little_endian = match data.get(..4) {
+        Some(header) if header[..2] == CDR_LE[..2] => true,
+        Some(header) if header[..2] == CDR_BE[..2] => false,
+        _ => return Err(DynamicError::CdrDecodeError("invalid header".into())),
+    };
+    let mut reader = Reader {
+        data,
+        pos: 4,
+        little_endian,
+    };
+    reader.read_message(description)
+}
+
+struct Writer {
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    fn align(&mut self, align: usize) {
+        while !(self.buf.len() - 4).is_multiple_of(align) {
+            self.buf.push(0);
+        }
+    }
+
+    fn write_u32(&mut self, v: u32) {
+        self.align(4);
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn write_message(&mut self, msg: &DynamicMessage) {
+        if msg.description().fields.is_empty() {
+            // `structure_needs_at_least_one_member`
+            self.buf.push(0);
+        }
+        for (_, value) in msg.fields() {
+            self.write_value(value);
+        }
+    }
+
+    fn write_value(&mut self, value: &Value) {
+        macro_rules! write_primitive {
+            ($v: expr) => {{
+                self.align(std::mem::size_of_val($v));
+                self.buf.extend_from_slice(&$v.to_le_bytes());
+            }};
+        }
+
+        match value {
+            Value::Bool(v) => self.buf.push(*v as u8),
+            Value::I8(v) => write_primitive!(v),
+            Value::I16(v) => write_primitive!(v),
+            Value::I32(v) => write_primitive!(v),
+            Value::I64(v) => write_primitive!(v),
+            Value::U8(v) => write_primitive!(v),
+            Value::U16(v) => write_primitive!(v),
+            Value::U32(v) => write_primitive!(v),
+            Value::U64(v) => write_primitive!(v),
+            Value::F32(v) => write_primitive!(v),
+            Value::F64(v) => write_primitive!(v),
+            Value::String(v) => {
+                self.write_u32(v.len() as u32 + 1);
+                self.buf.extend_from_slice(v.as_bytes());
+                self.buf.push(0);
+            }
+            // Wide characters are sent as 32-bit `wchar_t`
+            Value::WString(v) => {
+                self.write_u32(v.len() as u32);
+                for &c in v.as_slice() {
+                    self.write_u32(c.into());
+                }
+            }
+            Value::Message(v) => self.write_message(v),
+            Value::Array(v) => v.iter().for_each(|v| self.write_value(v)),
+            Value::Sequence(v) => {
+                self.write_u32(v.len() as u32);
+                v.iter().for_each(|v| self.write_value(v));
+            }
+        }
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    little_endian: bool,
+}
+
+macro_rules! define_read_primitive {
+    ($name: ident, $type: ty) => {
+        fn $name(&mut self) -> Result<$type, DynamicError> {
+            const SIZE: usize = std::mem::size_of::<$type>();
+            self.align(SIZE);
+            let bytes = self.take(SIZE)?.try_into().unwrap();
+            Ok(if self.little_endian {
+                <$type>::from_le_bytes(bytes)
+            } else {
+                <$type>::from_be_bytes(bytes)
+            })
+        }
+    };
+}
+
+impl<'a> Reader<'a> {
+    const fn align(&mut self, align: usize) {
+        self.pos += (align - (self.pos - 4) % align) % align;
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DynamicError> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + len)
+            .ok_or_else(|| DynamicError::CdrDecodeError("unexpected end of data".into()))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    define_read_primitive!(read_i8, i8);
+    define_read_primitive!(read_i16, i16);
+    define_read_primitive!(read_i32, i32);
+    define_read_primitive!(read_i64, i64);
+    define_read_primitive!(read_u8, u8);
+    define_read_primitive!(read_u16, u16);
+    define_read_primitive!(read_u32, u32);
+    define_read_primitive!(read_u64, u64);
+    define_read_primitive!(read_f32, f32);
+    define_read_primitive!(read_f64, f64);
+
+    fn read_len(&mut self) -> Result<usize, DynamicError> {
+        let len = self.read_u32()? as usize;
+        // Every element takes at least a byte, so a larger length can not be valid.
+        if len > self.data.len() - self.pos {
+            return Err(DynamicError::CdrDecodeError(format!(
+                "invalid length {}",
+                len
+            )));
+        }
+        Ok(len)
+    }
+
+    fn read_message(
+        &mut self,
+        description: &'static MessageDescription,
+    ) -> Result<DynamicMessage, DynamicError> {
+        if description.fields.is_empty() {
+            self.read_u8()?;
+        }
+        let values = description
+            .fields
+            .iter()
+            .map(|field| self.read_field(&field.r#type))
+            .collect::<Result<_, _>>()?;
+        Ok(DynamicMessage::from_values_unchecked(description, values))
+    }
+
+    fn read_field(&mut self, field_type: &FieldType) -> Result<Value, DynamicError> {
+        Ok(match field_type {
+            FieldType::Single(t) => self.read_value(t)?,
+            FieldType::Array { value_type, size } => Value::Array(
+                (0..*size)
+                    .map(|_| self.read_value(value_type))
+                    .collect::<Result<_, _>>()?,
+            ),
+            FieldType::Sequence { value_type } => {
+                let len = self.read_len()?;
+                Value::Sequence(
+                    (0..len)
+                        .map(|_| self.read_value(value_type))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            FieldType::BoundedSequence {
+                value_type,
+                max_size,
+            } => {
+                let len = self.read_len()?;
+                if len > *max_size {
+                    return Err(DynamicError::CdrDecodeError(format!(
+                        "sequence length {} exceeds the bound {}",
+                        len, max_size
+                    )));
+                }
+                Value::Sequence(
+                    (0..len)
+                        .map(|_| self.read_value(value_type))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+        })
+    }
+
+    fn read_value(&mut self, value_type: &ValueType) -> Result<Value, DynamicError> {
+        Ok(match value_type {
+            ValueType::BasicType(t) => match t {
+                BasicType::Bool => Value::Bool(self.read_u8()? != 0),
+                BasicType::I8 => Value::I8(self.read_i8()?),
+                BasicType::I16 => Value::I16(self.read_i16()?),
+                BasicType::I32 => Value::I32(self.read_i32()?),
+                BasicType::I64 => Value::I64(self.read_i64()?),
+                BasicType::U8 | BasicType::Char | BasicType::Byte => Value::U8(self.read_u8()?),
+                BasicType::U16 => Value::U16(self.read_u16()?),
+                BasicType::U32 => Value::U32(self.read_u32()?),
+                BasicType::U64 => Value::U64(self.read_u64()?),
+                BasicType::F32 => Value::F32(self.read_f32()?),
+                BasicType::F64 => Value::F64(self.read_f64()?),
+            },
+            ValueType::GenericString(t) if t.is_wide() => {
+                let len = self.read_len()?;
+                let chars = (0..len)
+                    .map(|_| self.read_u32().map(|c| c as u16))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Value::WString(U16String::from_vec(chars))
+            }
+            ValueType::GenericString(_) => {
+                let len = self.read_len()?;
+                let bytes = self.take(len)?;
+                let bytes = bytes.strip_suffix(&[0]).unwrap_or(bytes);
+                Value::String(
+                    String::from_utf8(bytes.to_vec())
+                        .map_err(|e| DynamicError::CdrDecodeError(e.to_string()))?,
+                )
+            }
+            ValueType::Message(t) => Value::Message(self.read_message(t)?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::introspection::{FieldDescription, GenericString};
+
+    const POINT: &MessageDescription = &MessageDescription {
+        package: "test_msgs",
+        namespace: "msg",
+        name: "Point",
+        size: 0,
+        align: 8,
+        fields: &[
+            FieldDescription {
+                name: "flag",
+                r#type: FieldType::Single(ValueType::BasicType(BasicType::Bool)),
+                default: None,
+                offset: 0,
+            },
+            FieldDescription {
+                name: "x",
+                r#type: FieldType::Single(ValueType::BasicType(BasicType::F64)),
+                default: Some("1.5"),
+                offset: 0,
+            },
+            FieldDescription {
+                name: "name",
+                r#type: FieldType::Single(ValueType::GenericString(GenericString::String)),
+                default: Some(r##"r#"ab"#"##),
+                offset: 0,
+            },
+            FieldDescription {
+                name: "values",
+                r#type: FieldType::Sequence {
+                    value_type: ValueType::BasicType(BasicType::I16),
+                },
+                default: Some("[1, -2]"),
+                offset: 0,
+            },
+        ],
+    };
+
+    #[test]
+    fn encode_layout() {
+        let msg = DynamicMessage::new(POINT).unwrap();
+        assert_eq!(
+            encode(&msg),
+            vec![
+                0x00, 0x01, 0x00, 0x00, // header
+                0x00, 0, 0, 0, 0, 0, 0, 0, // flag + padding
+                0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xf8, 0x3f, // x
+                0x03, 0x00, 0x00, 0x00, b'a', b'b', 0x00, 0, // name + padding
+                0x02, 0x00, 0x00, 0x00, 0x01, 0x00, 0xfe, 0xff, // values
+            ]
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut msg = DynamicMessage::new(POINT).unwrap();
+        msg.set("flag", true).unwrap();
+        msg.set("name", "hello").unwrap();
+        let decoded = decode(POINT, &encode(&msg)).unwrap();
+        assert_eq!(decoded, msg);
+    }
+
+    #[test]
+    fn decode_truncated_data() {
+        let data = encode(&DynamicMessage::new(POINT).unwrap());
+        assert!(decode(POINT, &data[..data.len() - 1]).is_err());
+        assert!(decode(POINT, &[]).is_err());
+    }
+}
@@ -0,0 +1,449 @@
+use rclrust_msg_types::literal;
+use widestring::U16String;
+
+use crate::error::DynamicError;
+use crate::introspection::{
+    BasicType, FieldDescription, FieldType, GenericString, MessageDescription, ValueType,
+};
+
+/// A value of a field of a [`DynamicMessage`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    /// `uint8`, `char` and `byte`
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    F32(f32),
+    F64(f64),
+    String(String),
+    WString(U16String),
+    Message(DynamicMessage),
+    Array(Vec<Self>),
+    Sequence(Vec<Self>),
+}
+
+macro_rules! impl_from_for_value {
+    ($type: ty, $variant: ident) => {
+        impl From<$type> for Value {
+            fn from(v: $type) -> Self {
+                Self::$variant(v)
+            }
+        }
+    };
+}
+
+impl_from_for_value!(bool, Bool);
+impl_from_for_value!(i8, I8);
+impl_from_for_value!(i16, I16);
+impl_from_for_value!(i32, I32);
+impl_from_for_value!(i64, I64);
+impl_from_for_value!(u8, U8);
+impl_from_for_value!(u16, U16);
+impl_from_for_value!(u32, U32);
+impl_from_for_value!(u64, U64);
+impl_from_for_value!(f32, F32);
+impl_from_for_value!(f64, F64);
+impl_from_for_value!(String, String);
+impl_from_for_value!(U16String, WString);
+impl_from_for_value!(DynamicMessage, Message);
+
+impl From<&str> for Value {
+    fn from(v: &str) -> Self {
+        Self::String(v.into())
+    }
+}
+
+impl Value {
+    /// Returns the zero value of `value_type`, or the default message for message types.
+    ///
+    /// Fails if a default value of the message type is invalid.
+    pub fn zero(value_type: &ValueType) -> Result<Self, DynamicError> {
+        Ok(match value_type {
+            ValueType::BasicType(t) => match t {
+                BasicType::Bool => Self::Bool(false),
+                BasicType::I8 => Self::I8(0),
+                BasicType::I16 => Self::I16(0),
+                BasicType::I32 => Self::I32(0),
+                BasicType::I64 => Self::I64(0),
+                BasicType::U8 | BasicType::Char | BasicType::Byte => Self::U8(0),
+                BasicType::U16 => Self::U16(0),
+                BasicType::U32 => Self::U32(0),
+                BasicType::U64 => Self::U64(0),
+                BasicType::F32 => Self::F32(0.),
+                BasicType::F64 => Self::F64(0.),
+            },
+            ValueType::GenericString(t) if t.is_wide() => Self::WString(U16String::new()),
+            ValueType::GenericString(_) => Self::String(String::new()),
+            ValueType::Message(t) => Self::Message(DynamicMessage::new(t)?),
+        })
+    }
+
+    fn zero_field(field_type: &FieldType) -> Result<Self, DynamicError> {
+        Ok(match field_type {
+            FieldType::Single(t) => Self::zero(t)?,
+            FieldType::Array { value_type, size } => {
+                Self::Array(vec![Self::zero(value_type)?; *size])
+            }
+            FieldType::Sequence { .. } | FieldType::BoundedSequence { .. } => {
+                Self::Sequence(Vec::new())
+            }
+        })
+    }
+
+    pub const fn as_message(&self) -> Option<&DynamicMessage> {
+        match self {
+            Self::Message(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements of an array or a sequence.
+    pub fn as_slice(&self) -> Option<&[Self]> {
+        match self {
+            Self::Array(v) | Self::Sequence(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    const fn as_mut_vec(&mut self) -> Option<&mut Vec<Self>> {
+        match self {
+            Self::Array(v) | Self::Sequence(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn conforms_to(&self, value_type: &ValueType) -> bool {
+        match (value_type, self) {
+            (ValueType::BasicType(t), v) => matches!(
+                (t, v),
+                (BasicType::Bool, Self::Bool(_))
+                    | (BasicType::I8, Self::I8(_))
+                    | (BasicType::I16, Self::I16(_))
+                    | (BasicType::I32, Self::I32(_))
+                    | (BasicType::I64, Self::I64(_))
+                    | (BasicType::U8, Self::U8(_))
+                    | (BasicType::Char, Self::U8(_))
+                    | (BasicType::Byte, Self::U8(_))
+                    | (BasicType::U16, Self::U16(_))
+                    | (BasicType::U32, Self::U32(_))
+                    | (BasicType::U64, Self::U64(_))
+                    | (BasicType::F32, Self::F32(_))
+                    | (BasicType::F64, Self::F64(_))
+            ),
+            (ValueType::GenericString(t), Self::String(v)) => match t {
+                GenericString::String => true,
+                GenericString::BoundedString(max_size) => v.len() <= *max_size,
+                _ => false,
+            },
+            (ValueType::GenericString(t), Self::WString(v)) => match t {
+                GenericString::WString => true,
+                GenericString::BoundedWString(max_size) => v.len() <= *max_size,
+                _ => false,
+            },
+            (ValueType::Message(t), Self::Message(v)) => t.is_same_type(v.description),
+            _ => false,
+        }
+    }
+
+    fn conforms_to_field(&self, field_type: &FieldType) -> bool {
+        match (field_type, self) {
+            (FieldType::Single(t), v) => v.conforms_to(t),
+            (FieldType::Array { value_type, size }, Self::Array(v)) => {
+                v.len() == *size && v.iter().all(|v| v.conforms_to(value_type))
+            }
+            (FieldType::Sequence { value_type }, Self::Sequence(v)) => {
+                v.iter().all(|v| v.conforms_to(value_type))
+            }
+            (
+                FieldType::BoundedSequence {
+                    value_type,
+                    max_size,
+                },
+                Self::Sequence(v),
+            ) => v.len() <= *max_size && v.iter().all(|v| v.conforms_to(value_type)),
+            _ => false,
+        }
+    }
+}
+
+/// A message whose type is known only at runtime
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynamicMessage {
+    description: &'static MessageDescription,
+    values: Vec<Value>,
+}
+
+impl DynamicMessage {
+    /// Creates a message filled with the default values of `description`.
+    ///
+    /// Fails if a default value of `description` or of its nested messages is invalid, which
+    /// [`TypeResolver`](crate::TypeResolver) rejects beforehand.
+    pub fn new(description: &'static MessageDescription) -> Result<Self, DynamicError> {
+        let values = description
+            .fields
+            .iter()
+            .map(|field| {
+                field.default.map_or_else(
+                    || Value::zero_field(&field.r#type),
+                    |default| {
+                        parse_default(&field.r#type, default).ok_or_else(|| {
+                            DynamicError::InvalidDefault {
+                                path: format!(
+                                    "{}/{}/{}.{}",
+                                    description.package,
+                                    description.namespace,
+                                    description.name,
+                                    field.name
+                                ),
+                                value: default.into(),
+                            }
+                        })
+                    },
+                )
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            description,
+            values,
+        })
+    }
+
+    pub const fn description(&self) -> &'static MessageDescription {
+        self.description
+    }
+
+    /// Returns an iterator over the fields and their values.
+    pub fn fields(&self) -> impl Iterator<Item = (&'static FieldDescription, &Value)> {
+        self.description.fields.iter().zip(self.values.iter())
+    }
+
+    /// Returns the value at `path`, e.g. `pose.position.x` or `points[1].x`.
+    pub fn get(&self, path: &str) -> Result<&Value, DynamicError> {
+        let mut msg = self;
+        let mut segments = parse_path(path)?.into_iter().peekable();
+        while let Some((name, index)) = segments.next() {
+            let value = &msg.values[msg.field_index(name, path)?];
+            let value = match index {
+                Some(index) => value
+                    .as_slice()
+                    .ok_or_else(|| DynamicError::InvalidPath(path.into()))?
+                    .get(index)
+                    .ok_or_else(|| DynamicError::IndexOutOfBounds {
+                        path: path.into(),
+                        index,
+                    })?,
+                None => value,
+            };
+            if segments.peek().is_none() {
+                return Ok(value);
+            }
+            msg = value
+                .as_message()
+                .ok_or_else(|| DynamicError::InvalidPath(path.into()))?;
+        }
+        Err(DynamicError::InvalidPath(path.into()))
+    }
+
+    /// Sets the value at `path` after checking that it conforms to the type of the field.
+    pub fn set<V: Into<Value>>(&mut self, path: &str, value: V) -> Result<(), DynamicError> {
+        let value = value.into();
+        let mut msg = self;
+        let mut segments = parse_path(path)?.into_iter().peekable();
+        while let Some((name, index)) = segments.next() {
+            let i = msg.field_index(name, path)?;
+            let field_type = &msg.description.fields[i].r#type;
+            let slot = &mut msg.values[i];
+            let slot = match index {
+                Some(index) => slot
+                    .as_mut_vec()
+                    .ok_or_else(|| DynamicError::InvalidPath(path.into()))?
+                    .get_mut(index)
+                    .ok_or_else(|| DynamicError::IndexOutOfBounds {
+                        path: path.into(),
+                        index,
+                    })?,
+                None => slot,
+            };
+            if segments.peek().is_none() {
+                let conforms = match index {
+                    Some(_) => value.conforms_to(field_type.value_type()),
+                    None => value.conforms_to_field(field_type),
+                };
+                if !conforms {
+                    return Err(DynamicError::TypeMismatch {
+                        path: path.into(),
+                        expected: match index {
+                            Some(_) => field_type.value_type().to_string(),
+                            None => field_type.to_string(),
+                        },
+                    });
+                }
+                *slot = value;
+                return Ok(());
+            }
+            msg = match slot {
+                Value::Message(v) => v,
+                _ => return Err(DynamicError::InvalidPath(path.into())),
+            };
+        }
+        Err(DynamicError::InvalidPath(path.into()))
+    }
+
+    fn field_index(&self, name: &str, path: &str) -> Result<usize, DynamicError> {
+        self.description
+            .fields
+            .iter()
+            .position(|field| field.name == name)
+            .ok_or_else(|| DynamicError::UnknownField(path.into()))
+    }
+
+    /// Creates a message from the values of all fields in order.
+    ///
+    /// The values are not checked against `description`.
+    pub const fn from_values_unchecked(
+        description: &'static MessageDescription,
+        values: Vec<Value>,
+    ) -> Self {
+        Self {
+            description,
+            values,
+        }
+    }
+}
+
+fn parse_path(path: &str) -> Result<Vec<(&str, Option<usize>)>, DynamicError> {
+    path.split('.')
+        .map(|segment| match segment.find('[') {
+            Some(pos) if segment.ends_with(']') => segment[pos + 1..segment.len() - 1]
+                .parse()
+                .map(|index| (&segment[..pos], Some(index)))
+                .map_err(|_| DynamicError::InvalidPath(path.into())),
+            Some(_) => Err(DynamicError::InvalidPath(path.into())),
+            None if segment.is_empty() => Err(DynamicError::InvalidPath(path.into())),
+            None => Ok((segment, None)),
+        })
+        .collect()
+}
+
+/// Parses a default value stored in [`FieldDescription::default`].
+pub(crate) fn parse_default(field_type: &FieldType, default: &str) -> Option<Value> {
+    match field_type {
+        FieldType::Single(t) => parse_default_value(t, default),
+        FieldType::Array { value_type, size } => {
+            let values = parse_default_sequence(value_type, default)?;
+            if values.len() == *size {
+                Some(Value::Array(values))
+            } else {
+                None
+            }
+        }
+        FieldType::Sequence { value_type } | FieldType::BoundedSequence { value_type, .. } => {
+            parse_default_sequence(value_type, default).map(Value::Sequence)
+        }
+    }
+}
+
+fn parse_default_value(value_type: &ValueType, s: &str) -> Option<Value> {
+    Some(match value_type {
+        ValueType::BasicType(t) => match t {
+            BasicType::Bool => Value::Bool(s.parse().ok()?),
+            BasicType::I8 => Value::I8(s.parse().ok()?),
+            BasicType::I16 => Value::I16(s.parse().ok()?),
+            BasicType::I32 => Value::I32(s.parse().ok()?),
+            BasicType::I64 => Value::I64(s.parse().ok()?),
+            BasicType::U8 | BasicType::Char | BasicType::Byte => Value::U8(s.parse().ok()?),
+            BasicType::U16 => Value::U16(s.parse().ok()?),
+            BasicType::U32 => Value::U32(s.parse().ok()?),
+            BasicType::U64 => Value::U64(s.parse().ok()?),
+            BasicType::F32 => Value::F32(s.parse().ok()?),
+            BasicType::F64 => Value::F64(s.parse().ok()?),
+        },
+        ValueType::GenericString(t) => {
+            let s = literal::parse_string(s)?;
+            if t.is_wide() {
+                Value::WString(U16String::from_str(s))
+            } else {
+                Value::String(s.into())
+            }
+        }
+        ValueType::Message(_) => return None,
+    })
+}
+
+fn parse_default_sequence(value_type: &ValueType, s: &str) -> Option<Vec<Value>> {
+    literal::parse_sequence(s)?
+        .into_iter()
+        .map(|s| parse_default_value(value_type, s))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_path_segments() {
+        assert_eq!(
+            parse_path("points[1].x").unwrap(),
+            vec![("points", Some(1)), ("x", None)]
+        );
+        assert!(parse_path("points[a]").is_err());
+        assert!(parse_path("pose..x").is_err());
+    }
+
+    #[test]
+    fn parse_default_values() {
+        let int_array = FieldType::Array {
+            value_type: ValueType::BasicType(BasicType::I8),
+            size: 3,
+        };
+        assert_eq!(
+            parse_default(&int_array, "[0, 127, -128]"),
+            Some(Value::Array(vec![
+                Value::I8(0),
+                Value::I8(127),
+                Value::I8(-128)
+            ]))
+        );
+        assert_eq!(parse_default(&int_array, "[0, 127]"), None);
+        assert_eq!(parse_default(&int_array, "[0, 128, 0]"), None);
+
+        let strings = FieldType::Sequence {
+            value_type: ValueType::GenericString(GenericString::String),
+        };
+        assert_eq!(
+            parse_default(&strings, r##"[r#"a, b"#, r#""#]"##),
+            Some(Value::Sequence(vec!["a, b".into(), "".into()]))
+        );
+    }
+
+    #[test]
+    fn invalid_default() {
+        const INVALID: &MessageDescription = &MessageDescription {
+            package: "test_msgs",
+            namespace: "msg",
+            name: "Invalid",
+            size: 4,
+            align: 4,
+            fields: &[FieldDescription {
+                name: "value",
+                r#type: FieldType::Single(ValueType::BasicType(BasicType::I32)),
+                default: Some("one"),
+                offset: 0,
+            }],
+        };
+        assert_eq!(
+            DynamicMessage::new(INVALID),
+            Err(DynamicError::InvalidDefault {
+                path: "test_msgs/msg/Invalid.value".into(),
+                value: "one".into(),
+            })
+        );
+    }
+}
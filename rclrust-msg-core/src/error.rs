@@ -0,0 +1,53 @@
+use thiserror::Error;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum DynamicError {
+    #[error("Unknown type: {0}")]
+    UnknownType(String),
+
+    #[error("Recursive type definition: {0}")]
+    RecursiveType(String),
+
+    #[error("Fail to parse default value of {path}: {value}")]
+    InvalidDefault { path: String, value: String },
+
+    #[error("Invalid field path: {0}")]
+    InvalidPath(String),
+
+    #[error("Unknown field: {0}")]
+    UnknownField(String),
+
+    #[error("Index {index} is out of bounds at {path}")]
+    IndexOutOfBounds { path: String, index: usize },
+
+    #[error("Type mismatch at {path}: expected {expected}")]
+    TypeMismatch { path: String, expected: String },
+
+    #[error("Value {value} is out of range at {path}")]
+    OutOfRange { path: String, value: String },
+
+    #[error("Fail to parse YAML: {0}")]
+    YamlParseError(String),
+
+    #[error("Fail to decode CDR data: {0}")]
+    CdrDecodeError(String),
+
+    #[error("Invalid introspection type support: {0}")]
+    InvalidTypeSupport(String),
+
+    #[error("Unsupported type: {0}")]
+    UnsupportedType(String),
+
+    #[error("Fail to allocate memory for {0}")]
+    AllocationError(String),
+}
+
+/// Errors of the conversions of `builtin_interfaces/msg/Time` and `Duration`
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum TimeError {
+    #[error("Seconds out of the range of int32")]
+    Overflow,
+
+    #[error("Negative time or duration")]
+    Negative,
+}
@@ -0,0 +1,188 @@
+use std::fmt;
+
+pub use rclrust_msg_types::{BasicType, GenericString};
+
+/// A runtime description of a message type
+#[derive(Debug, PartialEq)]
+pub struct MessageDescription {
+    /// The package name
+    /// e.g. `std_msgs`
+    pub package: &'static str,
+    /// `msg`, `srv` or `action`
+    pub namespace: &'static str,
+    /// The name of the message
+    /// e.g. `Header`
+    pub name: &'static str,
+    /// The size of the raw message in bytes
+    pub size: usize,
+    /// The alignment of the raw message in bytes
+    pub align: usize,
+    /// The list of the fields
+    pub fields: &'static [FieldDescription],
+}
+
+impl MessageDescription {
+    /// Returns the field named `name`, if any.
+    pub fn field(&self, name: &str) -> Option<&FieldDescription> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    /// Returns `true` if both describe the same message type.
+    pub fn is_same_type(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+            || (self.package == other.package
+                && self.namespace == other.namespace
+                && self.name == other.name)
+    }
+}
+
+impl fmt::Display for MessageDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}/{}", self.package, self.namespace, self.name)
+    }
+}
+
+/// A runtime description of a field of a message
+#[derive(Debug, PartialEq)]
+pub struct FieldDescription {
+    /// The name of the field as written in the interface file
+    pub name: &'static str,
+    /// The type of the field
+    pub r#type: FieldType,
+    /// The default value of the field (optional)
+    pub default: Option<&'static str>,
+    /// The offset of the field in the raw message in bytes
+    pub offset: usize,
+}
+
+/// A type which can be stored in a field or in an array/sequence
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValueType {
+    BasicType(BasicType),
+    GenericString(GenericString),
+    Message(&'static MessageDescription),
+}
+
+/// The type of a field
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldType {
+    Single(ValueType),
+    Array {
+        /// The type of the elements
+        value_type: ValueType,
+        /// The number of elements in the array
+        size: usize,
+    },
+    Sequence {
+        /// The type of the elements
+        value_type: ValueType,
+    },
+    BoundedSequence {
+        /// The type of the elements
+        value_type: ValueType,
+        /// The maximum number of elements in the sequence
+        max_size: usize,
+    },
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BasicType(t) => write!(f, "{}", t),
+            Self::GenericString(t) => write!(f, "{}", t),
+            Self::Message(t) => write!(f, "{}/{}", t.package, t.name),
+        }
+    }
+}
+
+impl FieldType {
+    pub const fn value_type(&self) -> &ValueType {
+        match self {
+            Self::Single(t) => t,
+            Self::Array { value_type, .. }
+            | Self::Sequence { value_type }
+            | Self::BoundedSequence { value_type, .. } => value_type,
+        }
+    }
+}
+
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Single(t) => write!(f, "{}", t),
+            Self::Array { value_type, size } => write!(f, "{}[{}]", value_type, size),
+            Self::Sequence { value_type } => write!(f, "{}[]", value_type),
+            Self::BoundedSequence {
+                value_type,
+                max_size,
+            } => write!(f, "{}[<={}]", value_type, max_size),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TIME: &MessageDescription = &MessageDescription {
+        package: "builtin_interfaces",
+        namespace: "msg",
+        name: "Time",
+        size: 8,
+        align: 4,
+        fields: &[
+            FieldDescription {
+                name: "sec",
+                r#type: FieldType::Single(ValueType::BasicType(BasicType::I32)),
+                default: None,
+                offset: 0,
+            },
+            FieldDescription {
+                name: "nanosec",
+                r#type: FieldType::Single(ValueType::BasicType(BasicType::U32)),
+                default: None,
+                offset: 4,
+            },
+        ],
+    };
+
+    const HEADER: &MessageDescription = &MessageDescription {
+        package: "std_msgs",
+        namespace: "msg",
+        name: "Header",
+        size: 32,
+        align: 8,
+        fields: &[
+            FieldDescription {
+                name: "stamp",
+                r#type: FieldType::Single(ValueType::Message(TIME)),
+                default: None,
+                offset: 0,
+            },
+            FieldDescription {
+                name: "frame_id",
+                r#type: FieldType::Single(ValueType::GenericString(GenericString::String)),
+                default: None,
+                offset: 8,
+            },
+        ],
+    };
+
+    #[test]
+    fn find_field() {
+        let stamp = HEADER.field("stamp").unwrap();
+        assert_eq!(stamp.offset, 0);
+        assert_eq!(stamp.r#type.value_type(), &ValueType::Message(TIME));
+        assert!(HEADER.field("seq").is_none());
+    }
+
+    #[test]
+    fn display_field_type() {
+        let field_type = FieldType::BoundedSequence {
+            value_type: ValueType::Message(TIME),
+            max_size: 3,
+        };
+        assert_eq!(field_type.to_string(), "builtin_interfaces/Time[<=3]");
+        assert_eq!(HEADER.to_string(), "std_msgs/msg/Header");
+    }
+}
@@ -0,0 +1,800 @@
+//! Bindings of `rosidl_typesupport_introspection_c` and a walker over raw messages using them
+
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::marker::PhantomData;
+use std::os::raw::{c_char, c_void};
+use std::sync::{Mutex, OnceLock};
+
+use crate::dynamic::{DynamicMessage, Value};
+use crate::error::DynamicError;
+use crate::introspection::{
+    BasicType, FieldDescription, FieldType, GenericString, MessageDescription, ValueType,
+};
+use crate::traits::{FFIToRust, MessageT};
+use crate::{FFIString, FFIWString};
+
+pub const TYPESUPPORT_IDENTIFIER: &str = "rosidl_typesupport_introspection_c";
+
+/// The member which rosidl adds to messages without fields
+const EMPTY_STRUCTURE_MEMBER: &str = "structure_needs_at_least_one_member";
+
+pub const ROS_TYPE_FLOAT: u8 = 1;
+pub const ROS_TYPE_DOUBLE: u8 = 2;
+pub const ROS_TYPE_LONG_DOUBLE: u8 = 3;
+pub const ROS_TYPE_CHAR: u8 = 4;
+pub const ROS_TYPE_WCHAR: u8 = 5;
+pub const ROS_TYPE_BOOLEAN: u8 = 6;
+pub const ROS_TYPE_OCTET: u8 = 7;
+pub const ROS_TYPE_UINT8: u8 = 8;
+pub const ROS_TYPE_INT8: u8 = 9;
+pub const ROS_TYPE_UINT16: u8 = 10;
+pub const ROS_TYPE_INT16: u8 = 11;
+pub const ROS_TYPE_UINT32: u8 = 12;
+pub const ROS_TYPE_INT32: u8 = 13;
+pub const ROS_TYPE_UINT64: u8 = 14;
+pub const ROS_TYPE_INT64: u8 = 15;
+pub const ROS_TYPE_STRING: u8 = 16;
+pub const ROS_TYPE_WSTRING: u8 = 17;
+pub const ROS_TYPE_MESSAGE: u8 = 18;
+
+/// `rosidl_message_type_support_t`
+#[repr(C)]
+#[derive(Debug)]
+pub struct MessageTypeSupport {
+    pub typesupport_identifier: *const c_char,
+    pub data: *const c_void,
+    pub func: Option<unsafe extern "C" fn(*const Self, *const c_char) -> *const Self>,
+}
+
+/// `rosidl_typesupport_introspection_c__MessageMember`
+///
+/// `fetch_function` and `assign_function` exist from Humble, which the build script detects from
+/// `$ROS_DISTRO`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct MessageMember {
+    pub name_: *const c_char,
+    pub type_id_: u8,
+    pub string_upper_bound_: usize,
+    pub members_: *const MessageTypeSupport,
+    pub is_array_: bool,
+    pub array_size_: usize,
+    pub is_upper_bound_: bool,
+    pub offset_: u32,
+    pub default_value_: *const c_void,
+    pub size_function: Option<unsafe extern "C" fn(*const c_void) -> usize>,
+    pub get_const_function: Option<unsafe extern "C" fn(*const c_void, usize) -> *const c_void>,
+    pub get_function: Option<unsafe extern "C" fn(*mut c_void, usize) -> *mut c_void>,
+    #[cfg(rosidl_member_accessors)]
+    pub fetch_function: Option<unsafe extern "C" fn(*const c_void, usize, *mut c_void)>,
+    #[cfg(rosidl_member_accessors)]
+    pub assign_function: Option<unsafe extern "C" fn(*mut c_void, usize, *const c_void)>,
+    pub resize_function: Option<unsafe extern "C" fn(*mut c_void, usize) -> bool>,
+}
+
+/// `rosidl_typesupport_introspection_c__MessageMembers`
+#[repr(C)]
+#[derive(Debug)]
+pub struct MessageMembers {
+    pub message_namespace_: *const c_char,
+    pub message_name_: *const c_char,
+    pub member_count_: u32,
+    pub size_of_: usize,
+    pub members_: *const MessageMember,
+    pub init_function: Option<unsafe extern "C" fn(*mut c_void, i32)>,
+    pub fini_function: Option<unsafe extern "C" fn(*mut c_void)>,
+}
+
+// The tables are immutable static data of the typesupport libraries.
+unsafe impl Sync for MessageTypeSupport {}
+unsafe impl Sync for MessageMember {}
+unsafe impl Sync for MessageMembers {}
+
+impl MessageMember {
+    pub fn name(&self) -> &str {
+        unsafe { CStr::from_ptr(self.name_) }
+            .to_str()
+            .expect("member name should be valid UTF-8")
+    }
+
+    fn nested(&self) -> Result<&'static MessageMembers, DynamicError> {
+        unsafe { MessageMembers::from_type_support(self.members_ as *const c_void) }
+    }
+}
+
+impl MessageMembers {
+    /// Returns the members table of an introspection type support handle.
+    ///
+    /// # Safety
+    ///
+    /// `type_support` must be null or point to a valid `rosidl_message_type_support_t`.
+    pub unsafe fn from_type_support(
+        type_support: *const c_void,
+    ) -> Result<&'static Self, DynamicError> {
+        let type_support = (type_support as *const MessageTypeSupport)
+            .as_ref()
+            .ok_or_else(|| DynamicError::InvalidTypeSupport("null handle".into()))?;
+        let identifier = CStr::from_ptr(type_support.typesupport_identifier).to_string_lossy();
+        if identifier != TYPESUPPORT_IDENTIFIER {
+            return Err(DynamicError::InvalidTypeSupport(identifier.into_owned()));
+        }
+        Ok(&*(type_support.data as *const Self))
+    }
+
+    pub const fn members(&self) -> &[MessageMember] {
+        if self.member_count_ == 0 {
+            return &[];
+        }
+        unsafe { std::slice::from_raw_parts(self.members_, self.member_count_ as usize) }
+    }
+
+    /// Returns the [`MessageDescription`] equivalent to this table.
+    ///
+    /// Descriptions are built once per table and live until the end of the program.
+    pub fn description(&'static self) -> Result<&'static MessageDescription, DynamicError> {
+        static DESCRIPTIONS: OnceLock<Mutex<HashMap<usize, &'static MessageDescription>>> =
+            OnceLock::new();
+
+        let key = self as *const Self as usize;
+        if let Some(description) = DESCRIPTIONS
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap()
+            .get(&key)
+        {
+            return Ok(description);
+        }
+
+        let members = match self.members() {
+            // `structure_needs_at_least_one_member` of an empty message
+            [member] if member.name() == EMPTY_STRUCTURE_MEMBER => &[],
+            members => members,
+        };
+        let fields = members
+            .iter()
+            .map(|member| {
+                Ok(FieldDescription {
+                    name: Box::leak(member.name().into()),
+                    r#type: member_field_type(member)?,
+                    default: None,
+                    offset: member.offset_ as usize,
+                })
+            })
+            .collect::<Result<Vec<_>, DynamicError>>()?;
+
+        let namespace = unsafe { CStr::from_ptr(self.message_namespace_) }.to_string_lossy();
+        let mut namespace = namespace.splitn(2, "__").map(|s| &*Box::leak(s.into()));
+        let description: &'static MessageDescription = Box::leak(Box::new(MessageDescription {
+            package: namespace.next().unwrap_or_default(),
+            namespace: namespace.next().unwrap_or_default(),
+            name: Box::leak(
+                unsafe { CStr::from_ptr(self.message_name_) }
+                    .to_string_lossy()
+                    .into(),
+            ),
+            size: self.size_of_,
+            align: fields
+                .iter()
+                .map(|field| field_align(&field.r#type))
+                .max()
+                .unwrap_or(1),
+            fields: Box::leak(fields.into_boxed_slice()),
+        }));
+
+        Ok(*DESCRIPTIONS
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_insert(description))
+    }
+}
+
+fn member_value_type(member: &MessageMember) -> Result<ValueType, DynamicError> {
+    let basic = |t| Ok(ValueType::BasicType(t));
+    match member.type_id_ {
+        ROS_TYPE_FLOAT => basic(BasicType::F32),
+        ROS_TYPE_DOUBLE => basic(BasicType::F64),
+        ROS_TYPE_CHAR => basic(BasicType::Char),
+        ROS_TYPE_WCHAR => basic(BasicType::U16),
+        ROS_TYPE_BOOLEAN => basic(BasicType::Bool),
+        ROS_TYPE_OCTET => basic(BasicType::Byte),
+        ROS_TYPE_UINT8 => basic(BasicType::U8),
+        ROS_TYPE_INT8 => basic(BasicType::I8),
+        ROS_TYPE_UINT16 => basic(BasicType::U16),
+        ROS_TYPE_INT16 => basic(BasicType::I16),
+        ROS_TYPE_UINT32 => basic(BasicType::U32),
+        ROS_TYPE_INT32 => basic(BasicType::I32),
+        ROS_TYPE_UINT64 => basic(BasicType::U64),
+        ROS_TYPE_INT64 => basic(BasicType::I64),
+        ROS_TYPE_STRING | ROS_TYPE_WSTRING => {
+            let wide = member.type_id_ == ROS_TYPE_WSTRING;
+            Ok(ValueType::GenericString(
+                match (wide, member.string_upper_bound_) {
+                    (false, 0) => GenericString::String,
+                    (true, 0) => GenericString::WString,
+                    (false, bound) => GenericString::BoundedString(bound),
+                    (true, bound) => GenericString::BoundedWString(bound),
+                },
+            ))
+        }
+        ROS_TYPE_MESSAGE => Ok(ValueType::Message(member.nested()?.description()?)),
+        type_id => Err(DynamicError::UnsupportedType(format!(
+            "{} (type id {})",
+            member.name(),
+            type_id
+        ))),
+    }
+}
+
+fn member_field_type(member: &MessageMember) -> Result<FieldType, DynamicError> {
+    let value_type = member_value_type(member)?;
+    Ok(
+        match (member.is_array_, member.array_size_, member.is_upper_bound_) {
+            (false, _, _) => FieldType::Single(value_type),
+            (true, 0, _) => FieldType::Sequence { value_type },
+            (true, max_size, true) => FieldType::BoundedSequence {
+                value_type,
+                max_size,
+            },
+            (true, size, false) => FieldType::Array { value_type, size },
+        },
+    )
+}
+
+const fn value_size(value_type: &ValueType) -> usize {
+    match value_type {
+        ValueType::BasicType(t) => t.size(),
+        ValueType::GenericString(t) if t.is_wide() => std::mem::size_of::<FFIWString>(),
+        ValueType::GenericString(_) => std::mem::size_of::<FFIString>(),
+        ValueType::Message(t) => t.size,
+    }
+}
+
+const fn field_align(field_type: &FieldType) -> usize {
+    match field_type {
+        FieldType::Single(t) | FieldType::Array { value_type: t, .. } => match t {
+            ValueType::BasicType(t) => t.align(),
+            ValueType::GenericString(_) => std::mem::align_of::<FFIString>(),
+            ValueType::Message(t) => t.align,
+        },
+        _ => std::mem::align_of::<usize>(),
+    }
+}
+
+/// The `data`, `size` and `capacity` triple shared by all rosidl sequences and strings
+#[repr(C)]
+struct RawBuffer {
+    data: *mut c_void,
+    size: usize,
+    capacity: usize,
+}
+
+// Linked by the generated code, or defined by the `mock` module
+extern "C" {
+    fn rosidl_runtime_c__String__assignn(
+        str: *mut FFIString,
+        value: *const c_char,
+        n: usize,
+    ) -> bool;
+    fn rosidl_runtime_c__U16String__assignn(
+        str: *mut FFIWString,
+        value: *const u16,
+        n: usize,
+    ) -> bool;
+}
+
+/// A raw message accessed through its introspection type support
+#[derive(Debug)]
+pub struct IntrospectedMessage<'a> {
+    members: &'static MessageMembers,
+    data: *mut c_void,
+    _marker: PhantomData<&'a mut c_void>,
+}
+
+impl<'a> IntrospectedMessage<'a> {
+    /// Wraps a raw message of a generated type.
+    pub fn new<T: MessageT>(raw: &'a mut T::Raw) -> Result<Self, DynamicError> {
+        unsafe { Self::from_raw_parts(T::introspection_type_support(), raw as *mut _ as *mut _) }
+    }
+
+    /// # Safety
+    ///
+    /// `data` must point to a message initialized by the rosidl C runtime
+    /// whose type is described by `type_support`, and must be valid for `'a`.
+    pub unsafe fn from_raw_parts(
+        type_support: *const c_void,
+        data: *mut c_void,
+    ) -> Result<Self, DynamicError> {
+        Ok(Self {
+            members: MessageMembers::from_type_support(type_support)?,
+            data,
+            _marker: PhantomData,
+        })
+    }
+
+    pub const fn members(&self) -> &'static MessageMembers {
+        self.members
+    }
+
+    /// Reads the whole message.
+    pub fn read(&self) -> Result<DynamicMessage, DynamicError> {
+        unsafe { read_message(self.members, self.data) }
+    }
+
+    /// Overwrites the whole message with `msg`.
+    ///
+    /// Nothing is written unless `msg` has the same type as the raw message and all its values
+    /// conform to the types, array sizes and bounds of the members.
+    pub fn write(&mut self, msg: &DynamicMessage) -> Result<(), DynamicError> {
+        let description = self.members.description()?;
+        if !description.is_same_type(msg.description()) {
+            return Err(DynamicError::TypeMismatch {
+                path: String::new(),
+                expected: description.to_string(),
+            });
+        }
+        check_message(self.members, msg, "")?;
+        unsafe { write_message(self.members, msg, self.data) }
+    }
+}
+
+/// Checks `msg` against the C members before anything is written, as the stores follow the
+/// variants of the values, and `msg` may have been created without checks.
+fn check_message(
+    members: &'static MessageMembers,
+    msg: &DynamicMessage,
+    path: &str,
+) -> Result<(), DynamicError> {
+    let description = members.description()?;
+    if msg.fields().count() != description.fields.len() {
+        return Err(DynamicError::TypeMismatch {
+            path: path.into(),
+            expected: description.to_string(),
+        });
+    }
+    for (member, (_, value)) in members.members().iter().zip(msg.fields()) {
+        let path = match path {
+            "" => member.name().to_string(),
+            _ => format!("{}.{}", path, member.name()),
+        };
+        let field_type = member_field_type(member)?;
+        let values = match (&field_type, value) {
+            (FieldType::Single(t), value) => {
+                check_value(member, t, value, &path)?;
+                continue;
+            }
+            (FieldType::Array { size, .. }, Value::Array(values)) if values.len() != *size => {
+                return Err(DynamicError::OutOfRange {
+                    path,
+                    value: format!("{} elements", values.len()),
+                })
+            }
+            (FieldType::BoundedSequence { max_size, .. }, Value::Sequence(values))
+                if values.len() > *max_size =>
+            {
+                return Err(DynamicError::OutOfRange {
+                    path,
+                    value: format!("{} elements", values.len()),
+                })
+            }
+            (FieldType::Array { .. }, Value::Array(values))
+            | (FieldType::Sequence { .. }, Value::Sequence(values))
+            | (FieldType::BoundedSequence { .. }, Value::Sequence(values)) => values,
+            _ => {
+                return Err(DynamicError::TypeMismatch {
+                    path,
+                    expected: field_type.to_string(),
+                })
+            }
+        };
+        for (i, value) in values.iter().enumerate() {
+            check_value(
+                member,
+                field_type.value_type(),
+                value,
+                &format!("{}[{}]", path, i),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn check_value(
+    member: &MessageMember,
+    value_type: &ValueType,
+    value: &Value,
+    path: &str,
+) -> Result<(), DynamicError> {
+    let len = match (value_type, value) {
+        (ValueType::GenericString(t), Value::String(v)) if !t.is_wide() => Some(v.len()),
+        (ValueType::GenericString(t), Value::WString(v)) if t.is_wide() => Some(v.len()),
+        _ => None,
+    };
+    match len {
+        Some(len) if member.string_upper_bound_ != 0 && len > member.string_upper_bound_ => {
+            return Err(DynamicError::OutOfRange {
+                path: path.into(),
+                value: format!("{} characters", len),
+            })
+        }
+        None if !value.conforms_to(value_type) => {
+            return Err(DynamicError::TypeMismatch {
+                path: path.into(),
+                expected: value_type.to_string(),
+            })
+        }
+        _ => {}
+    }
+    match value {
+        Value::Message(msg) => check_message(member.nested()?, msg, path),
+        _ => Ok(()),
+    }
+}
+
+unsafe fn read_message(
+    members: &'static MessageMembers,
+    data: *const c_void,
+) -> Result<DynamicMessage, DynamicError> {
+    let description = members.description()?;
+    let values = members
+        .members()
+        .iter()
+        .zip(description.fields)
+        .map(|(member, field)| {
+            let ptr = (data as *const u8).add(member.offset_ as usize);
+            match field.r#type {
+                FieldType::Single(ref t) => read_value(member, t, ptr),
+                FieldType::Array {
+                    ref value_type,
+                    size,
+                } => read_elements(member, value_type, ptr, size).map(Value::Array),
+                FieldType::Sequence { ref value_type }
+                | FieldType::BoundedSequence { ref value_type, .. } => {
+                    let seq = &*(ptr as *const RawBuffer);
+                    read_elements(member, value_type, seq.data as *const u8, seq.size)
+                        .map(Value::Sequence)
+                }
+            }
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(DynamicMessage::from_values_unchecked(description, values))
+}
+
+unsafe fn read_elements(
+    member: &MessageMember,
+    value_type: &ValueType,
+    ptr: *const u8,
+    len: usize,
+) -> Result<Vec<Value>, DynamicError> {
+    let size = value_size(value_type);
+    (0..len)
+        .map(|i| read_value(member, value_type, ptr.add(i * size)))
+        .collect()
+}
+
+unsafe fn read_value(
+    member: &MessageMember,
+    value_type: &ValueType,
+    ptr: *const u8,
+) -> Result<Value, DynamicError> {
+    Ok(match value_type {
+        ValueType::BasicType(t) => match t {
+            BasicType::Bool => Value::Bool(*(ptr as *const bool)),
+            BasicType::I8 => Value::I8(*(ptr as *const i8)),
+            BasicType::I16 => Value::I16(*(ptr as *const i16)),
+            BasicType::I32 => Value::I32(*(ptr as *const i32)),
+            BasicType::I64 => Value::I64(*(ptr as *const i64)),
+            BasicType::U8 | BasicType::Char | BasicType::Byte => Value::U8(*ptr),
+            BasicType::U16 => Value::U16(*(ptr as *const u16)),
+            BasicType::U32 => Value::U32(*(ptr as *const u32)),
+            BasicType::U64 => Value::U64(*(ptr as *const u64)),
+            BasicType::F32 => Value::F32(*(ptr as *const f32)),
+            BasicType::F64 => Value::F64(*(ptr as *const f64)),
+        },
+        ValueType::GenericString(t) if t.is_wide() => {
+            Value::WString((*(ptr as *const FFIWString)).to_rust())
+        }
+        ValueType::GenericString(_) => Value::String((*(ptr as *const FFIString)).to_rust()),
+        ValueType::Message(_) => Value::Message(read_message(member.nested()?, ptr as *const _)?),
+    })
+}
+
+unsafe fn write_message(
+    members: &'static MessageMembers,
+    msg: &DynamicMessage,
+    data: *mut c_void,
+) -> Result<(), DynamicError> {
+    for (member, (_, value)) in members.members().iter().zip(msg.fields()) {
+        let ptr = (data as *mut u8).add(member.offset_ as usize);
+        match value {
+            Value::Array(values) => write_elements(member, values, ptr)?,
+            Value::Sequence(values) => {
+                let resize = member.resize_function.ok_or_else(|| {
+                    DynamicError::InvalidTypeSupport(format!(
+                        "{} can not be resized",
+                        member.name()
+                    ))
+                })?;
+                if !resize(ptr as *mut c_void, values.len()) {
+                    return Err(DynamicError::AllocationError(member.name().into()));
+                }
+                let seq = &*(ptr as *const RawBuffer);
+                write_elements(member, values, seq.data as *mut u8)?;
+            }
+            value => write_value(member, value, ptr)?,
+        }
+    }
+    Ok(())
+}
+
+unsafe fn write_elements(
+    member: &MessageMember,
+    values: &[Value],
+    ptr: *mut u8,
+) -> Result<(), DynamicError> {
+    let size = value_size(member_field_type(member)?.value_type());
+    for (i, value) in values.iter().enumerate() {
+        write_value(member, value, ptr.add(i * size))?;
+    }
+    Ok(())
+}
+
+unsafe fn write_value(
+    member: &MessageMember,
+    value: &Value,
+    ptr: *mut u8,
+) -> Result<(), DynamicError> {
+    match value {
+        Value::Bool(v) => *(ptr as *mut bool) = *v,
+        Value::I8(v) => *(ptr as *mut i8) = *v,
+        Value::I16(v) => *(ptr as *mut i16) = *v,
+        Value::I32(v) => *(ptr as *mut i32) = *v,
+        Value::I64(v) => *(ptr as *mut i64) = *v,
+        Value::U8(v) => *ptr = *v,
+        Value::U16(v) => *(ptr as *mut u16) = *v,
+        Value::U32(v) => *(ptr as *mut u32) = *v,
+        Value::U64(v) => *(ptr as *mut u64) = *v,
+        Value::F32(v) => *(ptr as *mut f32) = *v,
+        Value::F64(v) => *(ptr as *mut f64) = *v,
+        Value::String(v) => {
+            if !rosidl_runtime_c__String__assignn(
+                ptr as *mut FFIString,
+                v.as_ptr() as *const c_char,
+                v.len(),
+            ) {
+                return Err(DynamicError::AllocationError(member.name().into()));
+            }
+        }
+        Value::WString(v) => {
+            if !rosidl_runtime_c__U16String__assignn(ptr as *mut FFIWString, v.as_ptr(), v.len()) {
+                return Err(DynamicError::AllocationError(member.name().into()));
+            }
+        }
+        Value::Message(v) => write_message(member.nested()?, v, ptr as *mut c_void)?,
+        Value::Array(_) | Value::Sequence(_) => {
+            return Err(DynamicError::UnsupportedType(member.name().into()))
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::traits::ZeroInit;
+
+    extern "C" {
+        fn free(ptr: *mut c_void);
+    }
+
+    #[repr(C)]
+    struct Sample {
+        flag: bool,
+        value: f64,
+        name: FFIString,
+    }
+
+    macro_rules! cstr {
+        ($s: expr) => {
+            concat!($s, "\0").as_ptr() as *const c_char
+        };
+    }
+
+    const fn member(name: *const c_char, type_id: u8, offset: usize) -> MessageMember {
+        MessageMember {
+            name_: name,
+            type_id_: type_id,
+            string_upper_bound_: 0,
+            members_: std::ptr::null(),
+            is_array_: false,
+            array_size_: 0,
+            is_upper_bound_: false,
+            offset_: offset as u32,
+            default_value_: std::ptr::null(),
+            size_function: None,
+            get_const_function: None,
+            get_function: None,
+            #[cfg(rosidl_member_accessors)]
+            fetch_function: None,
+            #[cfg(rosidl_member_accessors)]
+            assign_function: None,
+            resize_function: None,
+        }
+    }
+
+    static SAMPLE_MEMBERS: [MessageMember; 3] = [
+        member(cstr!("flag"), ROS_TYPE_BOOLEAN, 0),
+        member(cstr!("value"), ROS_TYPE_DOUBLE, 8),
+        member(cstr!("name"), ROS_TYPE_STRING, 16),
+    ];
+
+    static SAMPLE: MessageMembers = MessageMembers {
+        message_namespace_: cstr!("test_msgs__msg"),
+        message_name_: cstr!("Sample"),
+        member_count_: 3,
+        size_of_: std::mem::size_of::<Sample>(),
+        members_: SAMPLE_MEMBERS.as_ptr(),
+        init_function: None,
+        fini_function: None,
+    };
+
+    static SAMPLE_TYPE_SUPPORT: MessageTypeSupport = MessageTypeSupport {
+        typesupport_identifier: cstr!("rosidl_typesupport_introspection_c"),
+        data: &SAMPLE as *const MessageMembers as *const c_void,
+        func: None,
+    };
+
+    fn type_support() -> *const c_void {
+        &SAMPLE_TYPE_SUPPORT as *const MessageTypeSupport as *const c_void
+    }
+
+    #[test]
+    fn build_description() -> Result<(), DynamicError> {
+        let description =
+            unsafe { MessageMembers::from_type_support(type_support())? }.description()?;
+        assert_eq!(description.to_string(), "test_msgs/msg/Sample");
+        assert_eq!(description.size, std::mem::size_of::<Sample>());
+        assert_eq!(description.align, std::mem::align_of::<Sample>());
+        assert_eq!(description.fields[2].name, "name");
+        assert_eq!(
+            description.fields[2].r#type,
+            FieldType::Single(ValueType::GenericString(GenericString::String))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn empty_description() -> Result<(), DynamicError> {
+        static EMPTY_MEMBERS: [MessageMember; 1] = [member(
+            cstr!("structure_needs_at_least_one_member"),
+            ROS_TYPE_UINT8,
+            0,
+        )];
+        static EMPTY: MessageMembers = MessageMembers {
+            message_namespace_: cstr!("test_msgs__msg"),
+            message_name_: cstr!("Empty"),
+            member_count_: 1,
+            size_of_: 1,
+            members_: EMPTY_MEMBERS.as_ptr(),
+            init_function: None,
+            fini_function: None,
+        };
+
+        let description = EMPTY.description()?;
+        assert_eq!(description.size, 1);
+        assert!(description.fields.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn read_and_write() -> Result<(), DynamicError> {
+        let mut sample = Sample {
+            flag: true,
+            value: 1.5,
+            name: FFIString::zero_init(),
+        };
+        let mut view = unsafe {
+            IntrospectedMessage::from_raw_parts(type_support(), &mut sample as *mut _ as *mut _)?
+        };
+
+        let mut msg = view.read()?;
+        assert_eq!(msg.get("flag")?, &Value::Bool(true));
+        assert_eq!(msg.get("value")?, &Value::F64(1.5));
+        assert_eq!(msg.get("name")?, &Value::String("".into()));
+
+        msg.set("value", -2.0)?;
+        msg.set("name", "abc")?;
+        view.write(&msg)?;
+        assert_eq!(view.read()?, msg);
+
+        assert_eq!(sample.value, -2.0);
+        assert_eq!(unsafe { sample.name.to_rust() }, "abc");
+        unsafe { free((*(&mut sample.name as *mut FFIString as *mut RawBuffer)).data) };
+        Ok(())
+    }
+
+    #[test]
+    fn reject_mismatched_write() -> Result<(), DynamicError> {
+        let mut sample = Sample {
+            flag: true,
+            value: 1.5,
+            name: FFIString::zero_init(),
+        };
+        let mut view = unsafe {
+            IntrospectedMessage::from_raw_parts(type_support(), &mut sample as *mut _ as *mut _)?
+        };
+        let description = view.members().description()?;
+
+        let msg = DynamicMessage::from_values_unchecked(
+            description,
+            vec![
+                Value::Bool(false),
+                Value::String("abc".into()),
+                Value::F64(0.0),
+            ],
+        );
+        assert!(matches!(
+            view.write(&msg),
+            Err(DynamicError::TypeMismatch { path, .. }) if path == "value"
+        ));
+
+        let msg = DynamicMessage::from_values_unchecked(description, vec![Value::Bool(false)]);
+        assert!(matches!(
+            view.write(&msg),
+            Err(DynamicError::TypeMismatch { .. })
+        ));
+
+        assert!(sample.flag);
+        assert_eq!(sample.value, 1.5);
+        Ok(())
+    }
+
+    #[test]
+    fn member_layout() {
+        use crate::layout::{FieldLayout, StructLayout};
+
+        // `size_function`, `get_const_function`, `get_function`, `fetch_function`,
+        // `assign_function` and `resize_function`
+        const FUNCTIONS: usize = if cfg!(rosidl_member_accessors) { 6 } else { 4 };
+
+        let pointer = FieldLayout::new(
+            std::mem::size_of::<*const c_void>(),
+            std::mem::align_of::<*const c_void>(),
+        );
+        let size = FieldLayout::new(std::mem::size_of::<usize>(), std::mem::align_of::<usize>());
+        let mut fields = [pointer; 9 + FUNCTIONS];
+        fields[..9].copy_from_slice(&[
+            pointer,
+            FieldLayout::basic(BasicType::U8),
+            size,
+            pointer,
+            FieldLayout::basic(BasicType::Bool),
+            size,
+            FieldLayout::basic(BasicType::Bool),
+            FieldLayout::basic(BasicType::U32),
+            pointer,
+        ]);
+        let layout = StructLayout::new(fields);
+
+        assert_eq!(std::mem::size_of::<MessageMember>(), layout.size);
+        assert_eq!(std::mem::align_of::<MessageMember>(), layout.align);
+        assert_eq!(
+            std::mem::offset_of!(MessageMember, resize_function),
+            layout.offsets[8 + FUNCTIONS]
+        );
+    }
+
+    #[test]
+    fn reject_other_type_support() {
+        static OTHER: MessageTypeSupport = MessageTypeSupport {
+            typesupport_identifier: cstr!("rosidl_typesupport_c"),
+            data: std::ptr::null(),
+            func: None,
+        };
+        let result =
+            unsafe { MessageMembers::from_type_support(&OTHER as *const _ as *const c_void) };
+        assert!(matches!(result, Err(DynamicError::InvalidTypeSupport(_))));
+    }
+}
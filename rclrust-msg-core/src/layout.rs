@@ -0,0 +1,204 @@
+//! Layouts of the C structures generated by `rosidl_generator_c`
+//!
+//! The layouts are computed from the interface definitions with the rules of the C ABI rather than
+//! from the Rust types, so that the `_Raw` structures can be checked against them.
+
+use std::mem;
+
+use crate::introspection::BasicType;
+
+/// The size and the alignment of a field of a C structure
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldLayout {
+    pub size: usize,
+    pub align: usize,
+    /// Whether the field has padding bytes, i.e. is a structure with padding or an array of them
+    pub has_padding: bool,
+}
+
+impl FieldLayout {
+    /// `rosidl_runtime_c__String` and `rosidl_runtime_c__U16String`, i.e. `data`, `size` and
+    /// `capacity`
+    pub const STRING: Self = Self::SEQUENCE;
+    /// `rosidl_runtime_c__<type>__Sequence`, i.e. `data`, `size` and `capacity`
+    pub const SEQUENCE: Self = Self::new(
+        mem::size_of::<*const u8>() + 2 * mem::size_of::<usize>(),
+        mem::align_of::<*const u8>(),
+    );
+
+    pub const fn new(size: usize, align: usize) -> Self {
+        Self {
+            size,
+            align,
+            has_padding: false,
+        }
+    }
+
+    pub const fn basic(t: BasicType) -> Self {
+        Self::new(t.size(), t.align())
+    }
+
+    /// Returns the layout of an array of `len` elements of `self`.
+    pub const fn array(self, len: usize) -> Self {
+        Self {
+            size: self.size * len,
+            ..self
+        }
+    }
+}
+
+/// The layout of a C structure with `N` fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StructLayout<const N: usize> {
+    pub offsets: [usize; N],
+    pub size: usize,
+    pub align: usize,
+    /// Whether the structure has padding bytes between or in its fields or at its end
+    pub has_padding: bool,
+}
+
+impl<const N: usize> StructLayout<N> {
+    /// Lays out `fields` in order as C compilers do.
+    pub const fn new(fields: [FieldLayout; N]) -> Self {
+        let mut offsets = [0; N];
+        let (mut size, mut align) = (0, 1);
+        let mut has_padding = false;
+        let mut i = 0;
+        while i < N {
+            offsets[i] = align_up(size, fields[i].align);
+            has_padding |= offsets[i] != size || fields[i].has_padding;
+            size = offsets[i] + fields[i].size;
+            if fields[i].align > align {
+                align = fields[i].align;
+            }
+            i += 1;
+        }
+        Self {
+            offsets,
+            size: align_up(size, align),
+            align,
+            has_padding: has_padding || align_up(size, align) != size,
+        }
+    }
+
+    /// Returns the layout of a field of this structure.
+    pub const fn as_field(&self) -> FieldLayout {
+        FieldLayout {
+            size: self.size,
+            align: self.align,
+            has_padding: self.has_padding,
+        }
+    }
+}
+
+const fn align_up(offset: usize, align: usize) -> usize {
+    offset.div_ceil(align) * align
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[repr(C)]
+    struct Time {
+        sec: i32,
+        nanosec: u32,
+    }
+
+    #[repr(C)]
+    struct Sample {
+        flag: bool,
+        stamps: [Time; 2],
+        level: u8,
+        frame_id: (*const u8, usize, usize),
+        value: f64,
+    }
+
+    #[test]
+    fn c_layout() {
+        const TIME: StructLayout<2> = StructLayout::new([
+            FieldLayout::basic(BasicType::I32),
+            FieldLayout::basic(BasicType::U32),
+        ]);
+        const SAMPLE: StructLayout<5> = StructLayout::new([
+            FieldLayout::basic(BasicType::Bool),
+            TIME.as_field().array(2),
+            FieldLayout::basic(BasicType::U8),
+            FieldLayout::STRING,
+            FieldLayout::basic(BasicType::F64),
+        ]);
+
+        assert_eq!(TIME.size, mem::size_of::<Time>());
+        assert_eq!(TIME.offsets, [0, 4]);
+        assert_eq!(SAMPLE.size, mem::size_of::<Sample>());
+        assert_eq!(SAMPLE.align, mem::align_of::<Sample>());
+        assert_eq!(
+            SAMPLE.offsets,
+            [
+                mem::offset_of!(Sample, flag),
+                mem::offset_of!(Sample, stamps),
+                mem::offset_of!(Sample, level),
+                mem::offset_of!(Sample, frame_id),
+                mem::offset_of!(Sample, value),
+            ]
+        );
+    }
+
+    #[repr(C)]
+    struct Wide {
+        flag: bool,
+        count: i64,
+        level: u8,
+        ratio: f64,
+    }
+
+    #[test]
+    fn c_layout_of_64_bit_fields() {
+        // 64-bit types are aligned to 4 bytes on e.g. i686
+        const WIDE: StructLayout<4> = StructLayout::new([
+            FieldLayout::basic(BasicType::Bool),
+            FieldLayout::basic(BasicType::I64),
+            FieldLayout::basic(BasicType::U8),
+            FieldLayout::basic(BasicType::F64),
+        ]);
+
+        assert_eq!(WIDE.size, mem::size_of::<Wide>());
+        assert_eq!(WIDE.align, mem::align_of::<Wide>());
+        assert_eq!(
+            WIDE.offsets,
+            [
+                mem::offset_of!(Wide, flag),
+                mem::offset_of!(Wide, count),
+                mem::offset_of!(Wide, level),
+                mem::offset_of!(Wide, ratio),
+            ]
+        );
+    }
+
+    #[test]
+    fn padding() {
+        let field = FieldLayout::basic;
+        let padded = StructLayout::new([field(BasicType::U8), field(BasicType::U16)]);
+        assert!(padded.has_padding);
+        assert!(StructLayout::new([field(BasicType::U16), field(BasicType::U8)]).has_padding);
+        assert!(
+            !StructLayout::new([field(BasicType::U8).array(2), field(BasicType::U16)]).has_padding
+        );
+        assert!(StructLayout::new([padded.as_field().array(2)]).has_padding);
+        assert!(!StructLayout::new([field(BasicType::I32), field(BasicType::U32)]).has_padding);
+        assert!(!StructLayout::new([FieldLayout::STRING, FieldLayout::SEQUENCE]).has_padding);
+    }
+
+    #[test]
+    fn string_layout() {
+        assert_eq!(FieldLayout::STRING.size, mem::size_of::<crate::FFIString>());
+        assert_eq!(
+            FieldLayout::STRING.align,
+            mem::align_of::<crate::FFIWString>()
+        );
+        assert_eq!(
+            FieldLayout::SEQUENCE.size,
+            mem::size_of::<crate::FFISeq<u8>>()
+        );
+    }
+}
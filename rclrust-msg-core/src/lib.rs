@@ -5,11 +5,33 @@
     clippy::nursery
 )]
 
+#[cfg(feature = "chrono")]
+pub use chrono;
 pub use widestring;
 
+pub mod action;
+#[cfg(any(feature = "proptest", feature = "quickcheck"))]
+pub mod arbitrary;
+pub mod cdr;
+pub mod dynamic;
+pub mod error;
+pub mod introspection;
+pub mod introspection_c;
+pub mod layout;
+#[cfg(any(test, feature = "mock-rosidl"))]
+pub mod mock;
+pub mod proto;
+pub mod registry;
+pub mod resolver;
 pub mod sequence;
 pub mod string;
+pub mod time;
 pub mod traits;
+pub mod yaml;
+
+pub use action::GoalId;
+pub use dynamic::{DynamicMessage, Value};
+pub use resolver::TypeResolver;
 
 pub use sequence::{FFISeq, OwnedFFISeq, RefFFISeq};
 pub use string::{FFIString, FFIWString, OwnedFFIString, OwnedFFIWString};
@@ -0,0 +1,524 @@
+//! A stand-in for the rosidl C runtime and type supports
+//!
+//! With the `mock-rosidl` feature, the generated code defines `__init`, `__fini` and the type
+//! support handles of the interfaces on top of this module instead of linking the libraries
+//! generated by rosidl, so that it can be tested without a ROS installation. Memory is allocated
+//! with `malloc` like the C runtime does, so the rest of this crate handles it in the same way.
+//!
+//! The module is only compiled with the `mock-rosidl` feature of this crate, which defines the
+//! string functions of `rosidl_runtime_c` used by
+//! [`IntrospectedMessage`](crate::introspection_c::IntrospectedMessage) as well.
+
+use std::mem;
+use std::os::raw::{c_char, c_void};
+use std::ptr;
+
+use widestring::U16String;
+
+use crate::introspection_c::{MessageMember, MessageMembers, MessageTypeSupport};
+use crate::traits::ZeroInit;
+use crate::{FFISeq, FFIString, FFIWString};
+
+extern "C" {
+    fn calloc(count: usize, size: usize) -> *mut c_void;
+    fn realloc(ptr: *mut c_void, size: usize) -> *mut c_void;
+    fn free(ptr: *mut c_void);
+}
+
+/// A raw value initialized and finalized like the rosidl C runtime does
+pub trait MockRaw {
+    /// The Rust type of the value
+    type Value;
+
+    /// Initializes `self` to `value`, ignoring its current contents.
+    ///
+    /// # Safety
+    ///
+    /// The memory allocated for `self` must be released with [`MockRaw::fini`].
+    unsafe fn init(&mut self, value: &Self::Value);
+
+    /// Releases the memory allocated for `self`.
+    ///
+    /// # Safety
+    ///
+    /// `self` must have been initialized with [`MockRaw::init`].
+    unsafe fn fini(&mut self);
+}
+
+/// A raw message with a stand-in of its introspection type support
+pub trait MockMessage: MockRaw {
+    /// The handle of `rosidl_typesupport_introspection_c`
+    const INTROSPECTION: &'static MessageTypeSupport;
+}
+
+macro_rules! impl_mock_raw {
+    ($($t: ty),*) => {
+        $(
+            impl MockRaw for $t {
+                type Value = Self;
+
+                unsafe fn init(&mut self, value: &Self) {
+                    *self = *value;
+                }
+
+                unsafe fn fini(&mut self) {}
+            }
+        )*
+    };
+}
+
+impl_mock_raw!(bool, i8, i16, i32, i64, u8, u16, u32, u64, f32, f64);
+
+/// Allocates a null terminated copy of `chars`.
+unsafe fn alloc_chars<T: Copy>(chars: &[T]) -> *mut T {
+    let data = calloc(chars.len() + 1, mem::size_of::<T>()) as *mut T;
+    assert!(!data.is_null(), "calloc should succeed");
+    ptr::copy_nonoverlapping(chars.as_ptr(), data, chars.len());
+    data
+}
+
+impl MockRaw for FFIString {
+    type Value = String;
+
+    unsafe fn init(&mut self, value: &String) {
+        self.data = alloc_chars(value.as_bytes()) as *mut c_char;
+        self.size = value.len();
+        self.capacity = value.len() + 1;
+    }
+
+    unsafe fn fini(&mut self) {
+        free(self.data as *mut c_void);
+        *self = Self::zero_init();
+    }
+}
+
+impl MockRaw for FFIWString {
+    type Value = U16String;
+
+    unsafe fn init(&mut self, value: &U16String) {
+        self.data = alloc_chars(value.as_slice());
+        self.size = value.len();
+        self.capacity = value.len() + 1;
+    }
+
+    unsafe fn fini(&mut self) {
+        free(self.data as *mut c_void);
+        *self = Self::zero_init();
+    }
+}
+
+impl<T: MockRaw> MockRaw for FFISeq<T> {
+    type Value = Vec<T::Value>;
+
+    unsafe fn init(&mut self, value: &Vec<T::Value>) {
+        *self = Self::zero_init();
+        if value.is_empty() {
+            return;
+        }
+        self.data = calloc(value.len(), mem::size_of::<T>()) as *mut T;
+        assert!(!self.data.is_null(), "calloc should succeed");
+        for (i, v) in value.iter().enumerate() {
+            (*self.data.add(i)).init(v);
+        }
+        self.size = value.len();
+        self.capacity = value.len();
+    }
+
+    unsafe fn fini(&mut self) {
+        for i in 0..self.size {
+            (*self.data.add(i)).fini();
+        }
+        free(self.data as *mut c_void);
+        *self = Self::zero_init();
+    }
+}
+
+impl<T: MockRaw, const N: usize> MockRaw for [T; N] {
+    type Value = [T::Value; N];
+
+    unsafe fn init(&mut self, value: &[T::Value; N]) {
+        for (raw, v) in self.iter_mut().zip(value) {
+            raw.init(v);
+        }
+    }
+
+    unsafe fn fini(&mut self) {
+        for raw in self {
+            raw.fini();
+        }
+    }
+}
+
+/// Initializes `msg` to the default value like `<msg>__init`.
+///
+/// # Safety
+///
+/// `msg` must be null or valid for writes.
+pub unsafe fn init<T: MockRaw>(msg: *mut T) -> bool
+where
+    T::Value: Default,
+{
+    if msg.is_null() {
+        return false;
+    }
+    (*msg).init(&T::Value::default());
+    true
+}
+
+/// Finalizes `msg` like `<msg>__fini`.
+///
+/// # Safety
+///
+/// `msg` must be null or initialized with [`init`].
+pub unsafe fn fini<T: MockRaw>(msg: *mut T) {
+    if let Some(msg) = msg.as_mut() {
+        msg.fini();
+    }
+}
+
+/// Returns a handle of `rosidl_typesupport_c`, which only identifies the type.
+///
+/// Service and action handles share the layout of message handles.
+pub const fn typesupport_c() -> MessageTypeSupport {
+    MessageTypeSupport {
+        typesupport_identifier: "rosidl_typesupport_c\0".as_ptr() as *const c_char,
+        data: ptr::null(),
+        func: None,
+    }
+}
+
+/// Returns a handle of `rosidl_typesupport_introspection_c` with the table `members`.
+pub const fn typesupport_introspection_c(members: &'static MessageMembers) -> MessageTypeSupport {
+    MessageTypeSupport {
+        typesupport_identifier: "rosidl_typesupport_introspection_c\0".as_ptr() as *const c_char,
+        data: members as *const MessageMembers as *const c_void,
+        func: None,
+    }
+}
+
+const fn c_str(s: &'static str) -> *const c_char {
+    let bytes = s.as_bytes();
+    assert!(
+        !bytes.is_empty() && bytes[bytes.len() - 1] == 0,
+        "strings should be null terminated"
+    );
+    bytes.as_ptr() as *const c_char
+}
+
+unsafe extern "C" fn init_function<T: MockRaw>(msg: *mut c_void, _: i32)
+where
+    T::Value: Default,
+{
+    init(msg as *mut T);
+}
+
+unsafe extern "C" fn fini_function<T: MockRaw>(msg: *mut c_void) {
+    fini(msg as *mut T);
+}
+
+/// Returns the members table of the raw message `T`.
+///
+/// `namespace` and `name` must be null terminated, e.g. `"std_msgs__msg\0"` and `"Header\0"`.
+pub const fn members<T: MockRaw>(
+    namespace: &'static str,
+    name: &'static str,
+    members: &'static [MessageMember],
+) -> MessageMembers
+where
+    T::Value: Default,
+{
+    MessageMembers {
+        message_namespace_: c_str(namespace),
+        message_name_: c_str(name),
+        member_count_: members.len() as u32,
+        size_of_: mem::size_of::<T>(),
+        members_: if members.is_empty() {
+            ptr::null()
+        } else {
+            members.as_ptr()
+        },
+        init_function: Some(init_function::<T>),
+        fini_function: Some(fini_function::<T>),
+    }
+}
+
+const unsafe extern "C" fn array_size<const N: usize>(_: *const c_void) -> usize {
+    N
+}
+
+const unsafe extern "C" fn array_get_const<T>(array: *const c_void, index: usize) -> *const c_void {
+    (array as *const T).add(index) as *const c_void
+}
+
+const unsafe extern "C" fn array_get<T>(array: *mut c_void, index: usize) -> *mut c_void {
+    (array as *mut T).add(index) as *mut c_void
+}
+
+const unsafe extern "C" fn sequence_size<T>(seq: *const c_void) -> usize {
+    (*(seq as *const FFISeq<T>)).size
+}
+
+const unsafe extern "C" fn sequence_get_const<T>(
+    seq: *const c_void,
+    index: usize,
+) -> *const c_void {
+    (*(seq as *const FFISeq<T>)).data.add(index) as *const c_void
+}
+
+const unsafe extern "C" fn sequence_get<T>(seq: *mut c_void, index: usize) -> *mut c_void {
+    (*(seq as *mut FFISeq<T>)).data.add(index) as *mut c_void
+}
+
+/// Resizes a sequence to `size` default values like rosidl does.
+unsafe extern "C" fn sequence_resize<T: MockRaw>(seq: *mut c_void, size: usize) -> bool
+where
+    T::Value: Default,
+{
+    let seq = &mut *(seq as *mut FFISeq<T>);
+    seq.fini();
+    seq.init(
+        &std::iter::repeat_with(T::Value::default)
+            .take(size)
+            .collect(),
+    );
+    true
+}
+
+/// Replaces `*data` with a null terminated copy of `chars` like `rosidl_runtime_c` does.
+unsafe fn assign_chars<T: Copy + Default>(
+    data: &mut *mut T,
+    size: &mut usize,
+    capacity: &mut usize,
+    chars: &[T],
+) -> bool {
+    let new = realloc(
+        *data as *mut c_void,
+        (chars.len() + 1) * mem::size_of::<T>(),
+    ) as *mut T;
+    if new.is_null() {
+        return false;
+    }
+    ptr::copy_nonoverlapping(chars.as_ptr(), new, chars.len());
+    *new.add(chars.len()) = T::default();
+    *data = new;
+    *size = chars.len();
+    *capacity = chars.len() + 1;
+    true
+}
+
+/// `rosidl_runtime_c__String__assignn`
+///
+/// # Safety
+///
+/// `str` must be an initialized string and `value` must point to `n` characters.
+#[no_mangle]
+pub unsafe extern "C" fn rosidl_runtime_c__String__assignn(
+    str: *mut FFIString,
+    value: *const c_char,
+    n: usize,
+) -> bool {
+    match (str.as_mut(), value.is_null()) {
+        (Some(str), false) => assign_chars(
+            &mut str.data,
+            &mut str.size,
+            &mut str.capacity,
+            std::slice::from_raw_parts(value, n),
+        ),
+        _ => false,
+    }
+}
+
+/// `rosidl_runtime_c__U16String__assignn`
+///
+/// # Safety
+///
+/// `str` must be an initialized string and `value` must point to `n` characters.
+#[no_mangle]
+pub unsafe extern "C" fn rosidl_runtime_c__U16String__assignn(
+    str: *mut FFIWString,
+    value: *const u16,
+    n: usize,
+) -> bool {
+    match (str.as_mut(), value.is_null()) {
+        (Some(str), false) => assign_chars(
+            &mut str.data,
+            &mut str.size,
+            &mut str.capacity,
+            std::slice::from_raw_parts(value, n),
+        ),
+        _ => false,
+    }
+}
+
+impl MessageMember {
+    /// Returns an entry of a members table of a single value.
+    ///
+    /// `name` must be null terminated.
+    pub const fn new(name: &'static str, type_id: u8, offset: usize) -> Self {
+        Self {
+            name_: c_str(name),
+            type_id_: type_id,
+            string_upper_bound_: 0,
+            members_: ptr::null(),
+            is_array_: false,
+            array_size_: 0,
+            is_upper_bound_: false,
+            offset_: offset as u32,
+            default_value_: ptr::null(),
+            size_function: None,
+            get_const_function: None,
+            get_function: None,
+            #[cfg(rosidl_member_accessors)]
+            fetch_function: None,
+            #[cfg(rosidl_member_accessors)]
+            assign_function: None,
+            resize_function: None,
+        }
+    }
+
+    /// Sets the maximum length of a bounded string.
+    pub const fn string_upper_bound(mut self, bound: usize) -> Self {
+        self.string_upper_bound_ = bound;
+        self
+    }
+
+    /// Sets the introspection type support of a nested message.
+    pub const fn nested_type_support(mut self, type_support: &'static MessageTypeSupport) -> Self {
+        self.members_ = type_support;
+        self
+    }
+
+    /// Makes this entry an array of `N` values of `T`.
+    pub const fn array<T, const N: usize>(mut self) -> Self {
+        self.is_array_ = true;
+        self.array_size_ = N;
+        self.size_function = Some(array_size::<N>);
+        self.get_const_function = Some(array_get_const::<T>);
+        self.get_function = Some(array_get::<T>);
+        self
+    }
+
+    /// Makes this entry a sequence of `T`, which is bounded unless `max_size` is 0.
+    pub const fn sequence<T: MockRaw>(mut self, max_size: usize) -> Self
+    where
+        T::Value: Default,
+    {
+        self.is_array_ = true;
+        self.array_size_ = max_size;
+        self.is_upper_bound_ = max_size > 0;
+        self.size_function = Some(sequence_size::<T>);
+        self.get_const_function = Some(sequence_get_const::<T>);
+        self.get_function = Some(sequence_get::<T>);
+        self.resize_function = Some(sequence_resize::<T>);
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dynamic::Value;
+    use crate::introspection_c::{
+        IntrospectedMessage, ROS_TYPE_DOUBLE, ROS_TYPE_INT32, ROS_TYPE_STRING,
+    };
+    use crate::traits::FFIToRust;
+
+    #[repr(C)]
+    struct Sample {
+        name: FFIString,
+        values: FFISeq<f64>,
+        ids: [i32; 2],
+    }
+
+    #[derive(Debug, Default, PartialEq)]
+    struct SampleValue {
+        name: String,
+        values: Vec<f64>,
+        ids: [i32; 2],
+    }
+
+    impl MockRaw for Sample {
+        type Value = SampleValue;
+
+        unsafe fn init(&mut self, value: &SampleValue) {
+            self.name.init(&value.name);
+            self.values.init(&value.values);
+            self.ids.init(&value.ids);
+        }
+
+        unsafe fn fini(&mut self) {
+            self.name.fini();
+            self.values.fini();
+            self.ids.fini();
+        }
+    }
+
+    static MEMBERS: [MessageMember; 3] = [
+        MessageMember::new("name\0", ROS_TYPE_STRING, mem::offset_of!(Sample, name)),
+        MessageMember::new("values\0", ROS_TYPE_DOUBLE, mem::offset_of!(Sample, values))
+            .sequence::<f64>(0),
+        MessageMember::new("ids\0", ROS_TYPE_INT32, mem::offset_of!(Sample, ids)).array::<i32, 2>(),
+    ];
+    static SAMPLE: MessageMembers = members::<Sample>("test_msgs__msg\0", "Sample\0", &MEMBERS);
+    static INTROSPECTION: MessageTypeSupport = typesupport_introspection_c(&SAMPLE);
+
+    fn zero_init() -> Sample {
+        Sample {
+            name: FFIString::zero_init(),
+            values: FFISeq::zero_init(),
+            ids: [0; 2],
+        }
+    }
+
+    #[test]
+    fn init_and_fini() {
+        let mut sample = zero_init();
+        let value = SampleValue {
+            name: "abc".into(),
+            values: vec![1.0, 2.5],
+            ids: [3, 4],
+        };
+        unsafe {
+            sample.init(&value);
+            assert_eq!(sample.name.to_rust(), "abc");
+            assert_eq!(sample.values.to_rust(), vec![1.0, 2.5]);
+            assert_eq!(sample.ids, [3, 4]);
+
+            sample.fini();
+            assert!(sample.name.is_empty());
+            assert!(sample.values.as_slice().is_empty());
+
+            assert!(!init::<Sample>(ptr::null_mut()));
+            fini::<Sample>(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn introspection() -> Result<(), crate::error::DynamicError> {
+        let mut sample = zero_init();
+        assert!(unsafe { init(&mut sample) });
+
+        let type_support = &INTROSPECTION as *const MessageTypeSupport as *const c_void;
+        let members = unsafe { MessageMembers::from_type_support(type_support)? };
+        assert_eq!(members.size_of_, mem::size_of::<Sample>());
+        let size = members.members()[2].size_function.unwrap();
+        assert_eq!(unsafe { size(&sample.ids as *const _ as *const c_void) }, 2);
+
+        let mut view = unsafe {
+            IntrospectedMessage::from_raw_parts(type_support, &mut sample as *mut _ as *mut _)?
+        };
+        let mut msg = view.read()?;
+        assert_eq!(msg.get("values")?, &Value::Sequence(vec![]));
+        msg.set("name", "xyz")?;
+        msg.set(
+            "values",
+            Value::Sequence(vec![Value::F64(0.5), Value::F64(1.5), Value::F64(2.5)]),
+        )?;
+        view.write(&msg)?;
+        assert_eq!(view.read()?, msg);
+
+        assert_eq!(unsafe { sample.values.to_rust() }, vec![0.5, 1.5, 2.5]);
+        unsafe { fini(&mut sample) };
+        Ok(())
+    }
+}
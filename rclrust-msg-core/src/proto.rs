@@ -0,0 +1,132 @@
+//! Conversion of messages to and from their Protocol Buffers representation
+//!
+//! Generated messages implement [`ProtoConvert`] with the `prost` feature of the crate
+//! containing them, converting into `<Message>_Proto` types deriving `prost::Message`.
+
+use widestring::U16String;
+
+/// Conversion between a Rust value and its Protocol Buffers representation
+pub trait ProtoConvert: Sized {
+    type Proto;
+
+    fn into_proto(self) -> Self::Proto;
+
+    fn from_proto(proto: Self::Proto) -> Self;
+}
+
+macro_rules! impl_proto_convert {
+    ($type: ty, $proto: ty) => {
+        impl ProtoConvert for $type {
+            type Proto = $proto;
+
+            fn into_proto(self) -> Self::Proto {
+                self.into()
+            }
+
+            /// Out of range values are truncated.
+            #[allow(clippy::cast_possible_truncation, clippy::cast_lossless)]
+            fn from_proto(proto: Self::Proto) -> Self {
+                proto as Self
+            }
+        }
+    };
+}
+
+impl_proto_convert!(i8, i32);
+impl_proto_convert!(i16, i32);
+impl_proto_convert!(i32, i32);
+impl_proto_convert!(i64, i64);
+impl_proto_convert!(u8, u32);
+impl_proto_convert!(u16, u32);
+impl_proto_convert!(u32, u32);
+impl_proto_convert!(u64, u64);
+impl_proto_convert!(f32, f32);
+impl_proto_convert!(f64, f64);
+
+impl ProtoConvert for bool {
+    type Proto = Self;
+
+    fn into_proto(self) -> Self::Proto {
+        self
+    }
+
+    fn from_proto(proto: Self::Proto) -> Self {
+        proto
+    }
+}
+
+impl ProtoConvert for String {
+    type Proto = Self;
+
+    fn into_proto(self) -> Self::Proto {
+        self
+    }
+
+    fn from_proto(proto: Self::Proto) -> Self {
+        proto
+    }
+}
+
+impl ProtoConvert for U16String {
+    type Proto = String;
+
+    fn into_proto(self) -> Self::Proto {
+        self.to_string_lossy()
+    }
+
+    fn from_proto(proto: Self::Proto) -> Self {
+        Self::from_str(&proto)
+    }
+}
+
+impl<T: ProtoConvert> ProtoConvert for Vec<T> {
+    type Proto = Vec<T::Proto>;
+
+    fn into_proto(self) -> Self::Proto {
+        self.into_iter().map(T::into_proto).collect()
+    }
+
+    fn from_proto(proto: Self::Proto) -> Self {
+        proto.into_iter().map(T::from_proto).collect()
+    }
+}
+
+impl<T: ProtoConvert + Default, const N: usize> ProtoConvert for [T; N] {
+    type Proto = Vec<T::Proto>;
+
+    fn into_proto(self) -> Self::Proto {
+        IntoIterator::into_iter(self).map(T::into_proto).collect()
+    }
+
+    fn from_proto(proto: Self::Proto) -> Self {
+        array_from_vec(proto.into_iter().map(T::from_proto).collect())
+    }
+}
+
+/// Converts a repeated field into an array, truncating extra elements and filling missing ones
+/// with their defaults.
+pub fn array_from_vec<T: Default, const N: usize>(v: Vec<T>) -> [T; N] {
+    let mut iter = v.into_iter();
+    std::array::from_fn(|_| iter.next().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn primitives() {
+        assert_eq!((-3_i8).into_proto(), -3_i32);
+        assert_eq!(i8::from_proto(-3), -3);
+        assert_eq!(u8::from_proto(0x1ff), 0xff);
+        assert_eq!(U16String::from_str("ros").into_proto(), String::from("ros"));
+    }
+
+    #[test]
+    fn arrays() {
+        assert_eq!([1_u16, 2].into_proto(), vec![1_u32, 2]);
+        assert_eq!(<[u16; 3]>::from_proto(vec![1, 2]), [1, 2, 0]);
+        assert_eq!(<[u16; 1]>::from_proto(vec![1, 2]), [1]);
+        assert_eq!(array_from_vec::<u8, 2>(vec![7]), [7, 0]);
+    }
+}
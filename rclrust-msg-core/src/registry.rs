@@ -0,0 +1,276 @@
+//! Lookup of generated types by their ROS type names at runtime
+
+use std::collections::BTreeMap;
+use std::os::raw::c_void;
+use std::sync::{OnceLock, RwLock};
+
+use crate::cdr;
+use crate::dynamic::{DynamicMessage, Value};
+use crate::error::DynamicError;
+use crate::introspection::MessageDescription;
+use crate::traits::{
+    ActionT, DynamicValue, MessageIntrospection, MessageT, ServiceEventT, ServiceT,
+};
+use crate::yaml::{self, YamlOptions};
+
+/// Functions of a message type
+#[derive(Debug, Clone, Copy)]
+pub struct MessageTypeInfo {
+    pub type_name: &'static str,
+    pub type_support: fn() -> *const c_void,
+    pub introspection_type_support: fn() -> *const c_void,
+    pub description: &'static MessageDescription,
+    /// Creates the message in the state of its `Default`.
+    pub default: fn() -> DynamicMessage,
+    pub cdr_encode: fn(&DynamicMessage) -> Vec<u8>,
+    pub cdr_decode: fn(&[u8]) -> Result<DynamicMessage, DynamicError>,
+    pub to_yaml: fn(&DynamicMessage, &YamlOptions) -> String,
+    /// Parses YAML, keeping the default values of unspecified fields.
+    pub from_yaml: fn(&str) -> Result<DynamicMessage, DynamicError>,
+}
+
+impl MessageTypeInfo {
+    pub fn of<T>() -> Self
+    where
+        T: MessageT + MessageIntrospection + DynamicValue,
+    {
+        Self {
+            type_name: T::TYPE_NAME,
+            type_support: T::type_support,
+            introspection_type_support: T::introspection_type_support,
+            description: T::DESCRIPTION,
+            default: default_message::<T>,
+            cdr_encode: cdr::encode,
+            cdr_decode: |data| cdr::decode(T::DESCRIPTION, data),
+            to_yaml: yaml::to_yaml,
+            from_yaml: |s| {
+                let mut msg = default_message::<T>();
+                yaml::apply_yaml(&mut msg, s)?;
+                Ok(msg)
+            },
+        }
+    }
+}
+
+fn default_message<T: DynamicValue + Default>() -> DynamicMessage {
+    match T::default().to_value() {
+        Value::Message(msg) => msg,
+        _ => unreachable!("messages are converted to `Value::Message`"),
+    }
+}
+
+/// Functions of a service type
+#[derive(Debug, Clone, Copy)]
+pub struct ServiceTypeInfo {
+    pub type_name: &'static str,
+    pub type_support: fn() -> *const c_void,
+    pub request: MessageTypeInfo,
+    pub response: MessageTypeInfo,
+    /// `<Service>_Event` of distros which publish the events of services
+    pub event: Option<MessageTypeInfo>,
+}
+
+impl ServiceTypeInfo {
+    pub fn of<T>() -> Self
+    where
+        T: ServiceT,
+        T::Request: MessageIntrospection + DynamicValue,
+        T::Response: MessageIntrospection + DynamicValue,
+    {
+        Self {
+            type_name: T::TYPE_NAME,
+            type_support: T::type_support,
+            request: MessageTypeInfo::of::<T::Request>(),
+            response: MessageTypeInfo::of::<T::Response>(),
+            event: None,
+        }
+    }
+
+    /// Returns the functions of `T` including its event message.
+    pub fn with_event<T>() -> Self
+    where
+        T: ServiceEventT,
+        T::Request: MessageIntrospection + DynamicValue,
+        T::Response: MessageIntrospection + DynamicValue,
+        T::Event: MessageIntrospection + DynamicValue,
+    {
+        Self {
+            event: Some(MessageTypeInfo::of::<T::Event>()),
+            ..Self::of::<T>()
+        }
+    }
+}
+
+/// Functions of an action type
+#[derive(Debug, Clone, Copy)]
+pub struct ActionTypeInfo {
+    pub type_name: &'static str,
+    pub type_support: fn() -> *const c_void,
+    pub goal: MessageTypeInfo,
+    pub result: MessageTypeInfo,
+    pub feedback: MessageTypeInfo,
+    pub send_goal: ServiceTypeInfo,
+    pub get_result: ServiceTypeInfo,
+    pub feedback_message: MessageTypeInfo,
+}
+
+impl ActionTypeInfo {
+    pub fn of<T>() -> Self
+    where
+        T: ActionT,
+        T::Goal: MessageIntrospection + DynamicValue,
+        T::Result: MessageIntrospection + DynamicValue,
+        T::Feedback: MessageIntrospection + DynamicValue,
+        <T::SendGoal as ServiceT>::Request: MessageIntrospection + DynamicValue,
+        <T::SendGoal as ServiceT>::Response: MessageIntrospection + DynamicValue,
+        <T::GetResult as ServiceT>::Request: MessageIntrospection + DynamicValue,
+        <T::GetResult as ServiceT>::Response: MessageIntrospection + DynamicValue,
+        T::FeedbackMessage: MessageIntrospection + DynamicValue,
+    {
+        Self {
+            type_name: T::TYPE_NAME,
+            type_support: T::type_support,
+            goal: MessageTypeInfo::of::<T::Goal>(),
+            result: MessageTypeInfo::of::<T::Result>(),
+            feedback: MessageTypeInfo::of::<T::Feedback>(),
+            send_goal: ServiceTypeInfo::of::<T::SendGoal>(),
+            get_result: ServiceTypeInfo::of::<T::GetResult>(),
+            feedback_message: MessageTypeInfo::of::<T::FeedbackMessage>(),
+        }
+    }
+}
+
+/// Generated types indexed by their full names, e.g. `geometry_msgs/msg/Twist`
+#[derive(Debug, Default)]
+pub struct TypeRegistry {
+    messages: BTreeMap<&'static str, MessageTypeInfo>,
+    services: BTreeMap<&'static str, ServiceTypeInfo>,
+    actions: BTreeMap<&'static str, ActionTypeInfo>,
+}
+
+impl TypeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_message(&mut self, info: MessageTypeInfo) {
+        self.messages.insert(info.type_name, info);
+    }
+
+    /// Adds a service together with its request, response and event messages.
+    pub fn add_service(&mut self, info: ServiceTypeInfo) {
+        self.add_message(info.request);
+        self.add_message(info.response);
+        if let Some(event) = info.event {
+            self.add_message(event);
+        }
+        self.services.insert(info.type_name, info);
+    }
+
+    /// Adds an action together with its messages and services.
+    pub fn add_action(&mut self, info: ActionTypeInfo) {
+        self.add_message(info.goal);
+        self.add_message(info.result);
+        self.add_message(info.feedback);
+        self.add_service(info.send_goal);
+        self.add_service(info.get_result);
+        self.add_message(info.feedback_message);
+        self.actions.insert(info.type_name, info);
+    }
+
+    /// Returns a message type by its full name, e.g. `std_msgs/msg/String`.
+    pub fn message(&self, type_name: &str) -> Option<&MessageTypeInfo> {
+        self.messages.get(type_name)
+    }
+
+    pub fn service(&self, type_name: &str) -> Option<&ServiceTypeInfo> {
+        self.services.get(type_name)
+    }
+
+    pub fn action(&self, type_name: &str) -> Option<&ActionTypeInfo> {
+        self.actions.get(type_name)
+    }
+
+    pub fn messages(&self) -> impl Iterator<Item = &MessageTypeInfo> {
+        self.messages.values()
+    }
+
+    pub fn services(&self) -> impl Iterator<Item = &ServiceTypeInfo> {
+        self.services.values()
+    }
+
+    pub fn actions(&self) -> impl Iterator<Item = &ActionTypeInfo> {
+        self.actions.values()
+    }
+}
+
+/// Returns the registry shared in the process.
+///
+/// Crates with generated packages register them by their `register` functions.
+pub fn global() -> &'static RwLock<TypeRegistry> {
+    static REGISTRY: OnceLock<RwLock<TypeRegistry>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The description behind the functions of the test types, which are all empty messages
+    static EMPTY: MessageDescription = MessageDescription {
+        package: "test_msgs",
+        namespace: "msg",
+        name: "Empty",
+        size: 1,
+        align: 1,
+        fields: &[],
+    };
+
+    fn message(type_name: &'static str, name: &'static str) -> MessageTypeInfo {
+        let description: &'static MessageDescription = Box::leak(Box::new(MessageDescription {
+            name,
+            namespace: "srv",
+            ..EMPTY
+        }));
+        MessageTypeInfo {
+            type_name,
+            type_support: std::ptr::null,
+            introspection_type_support: std::ptr::null,
+            description,
+            default: || DynamicMessage::from_values_unchecked(&EMPTY, Vec::new()),
+            cdr_encode: cdr::encode,
+            cdr_decode: |data| cdr::decode(&EMPTY, data),
+            to_yaml: yaml::to_yaml,
+            from_yaml: |s| yaml::parse_yaml(&EMPTY, s),
+        }
+    }
+
+    #[test]
+    fn add_service() {
+        let mut registry = TypeRegistry::new();
+        registry.add_service(ServiceTypeInfo {
+            type_name: "test_msgs/srv/Empty",
+            type_support: std::ptr::null,
+            request: message("test_msgs/srv/Empty_Request", "Empty_Request"),
+            response: message("test_msgs/srv/Empty_Response", "Empty_Response"),
+            event: Some(message("test_msgs/srv/Empty_Event", "Empty_Event")),
+        });
+
+        assert!(registry.service("test_msgs/srv/Empty").is_some());
+        assert!(registry.action("test_msgs/srv/Empty").is_none());
+        assert_eq!(
+            registry.messages().map(|m| m.type_name).collect::<Vec<_>>(),
+            [
+                "test_msgs/srv/Empty_Event",
+                "test_msgs/srv/Empty_Request",
+                "test_msgs/srv/Empty_Response"
+            ]
+        );
+        assert_eq!(
+            registry
+                .message("test_msgs/srv/Empty_Request")
+                .map(|m| m.description.name),
+            Some("Empty_Request")
+        );
+    }
+}
@@ -0,0 +1,382 @@
+use std::collections::HashMap;
+use std::mem;
+use std::sync::Mutex;
+
+pub use rclrust_msg_types::Distro;
+use rclrust_msg_types::{Action, MemberType, Message, NestableType, Service};
+
+use crate::dynamic::parse_default;
+use crate::error::DynamicError;
+use crate::introspection::{FieldDescription, FieldType, MessageDescription, ValueType};
+use crate::{FFISeq, FFIString, FFIWString};
+
+/// Builds [`MessageDescription`]s from parsed interface definitions.
+///
+/// Built descriptions are interned for the rest of the program
+/// so that they can be used in the same way as the generated ones.
+#[derive(Debug)]
+pub struct TypeResolver {
+    /// The distro whose types are synthesized for services and actions
+    distro: Distro,
+    messages: HashMap<String, Message>,
+    descriptions: Mutex<HashMap<String, &'static MessageDescription>>,
+}
+
+impl Default for TypeResolver {
+    fn default() -> Self {
+        Self::with_distro(Distro::Foxy)
+    }
+}
+
+impl TypeResolver {
+    /// Creates a resolver of the types of Foxy, where services have no events.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a resolver which synthesizes the types of services and actions like `distro`, e.g.
+    /// `<Service>_Event` from Iron on.
+    pub fn with_distro(distro: Distro) -> Self {
+        Self {
+            distro,
+            messages: HashMap::new(),
+            descriptions: Mutex::default(),
+        }
+    }
+
+    pub fn add_message(&mut self, msg: Message) {
+        self.insert("msg", msg);
+    }
+
+    pub fn add_service(&mut self, srv: Service) {
+        self.insert_service("srv", srv);
+    }
+
+    pub fn add_action(&mut self, action: Action) {
+        self.insert_service("action", action.send_goal_srv(self.distro));
+        self.insert_service("action", action.get_result_srv(self.distro));
+        self.insert("action", action.feedback_message_msg(self.distro));
+        self.insert("action", action.goal);
+        self.insert("action", action.result);
+        self.insert("action", action.feedback);
+    }
+
+    /// Registers an already built description, e.g. the one of a generated type.
+    pub fn add_description(&self, description: &'static MessageDescription) {
+        self.descriptions
+            .lock()
+            .unwrap()
+            .insert(description.to_string(), description);
+    }
+
+    /// Returns the description of `type_name`.
+    ///
+    /// `type_name` is `<package>/<namespace>/<name>` or `<package>/<name>` for messages.
+    pub fn resolve(&self, type_name: &str) -> Result<&'static MessageDescription, DynamicError> {
+        let key = match type_name.split('/').collect::<Vec<_>>().as_slice() {
+            [package, name] => format!("{}/msg/{}", package, name),
+            [_, _, _] => type_name.into(),
+            _ => return Err(DynamicError::UnknownType(type_name.into())),
+        };
+
+        let mut descriptions = self.descriptions.lock().unwrap();
+        self.build(&key, &mut descriptions, &mut Vec::new())
+    }
+
+    fn insert_service(&mut self, namespace: &str, srv: Service) {
+        if let Some(event) = srv.event_msg(namespace, self.distro) {
+            self.insert(namespace, event);
+        }
+        self.insert(namespace, srv.request);
+        self.insert(namespace, srv.response);
+    }
+
+    fn insert(&mut self, namespace: &str, msg: Message) {
+        self.messages
+            .insert(format!("{}/{}/{}", msg.package, namespace, msg.name), msg);
+    }
+
+    fn build(
+        &self,
+        key: &str,
+        descriptions: &mut HashMap<String, &'static MessageDescription>,
+        visiting: &mut Vec<String>,
+    ) -> Result<&'static MessageDescription, DynamicError> {
+        if let Some(description) = descriptions.get(key) {
+            return Ok(description);
+        }
+        if visiting.iter().any(|v| v == key) {
+            return Err(DynamicError::RecursiveType(key.into()));
+        }
+        let msg = self
+            .messages
+            .get(key)
+            .ok_or_else(|| DynamicError::UnknownType(key.into()))?;
+        visiting.push(key.into());
+
+        let mut fields = Vec::with_capacity(msg.members.len());
+        let (mut size, mut align) = (0, 1);
+        for member in &msg.members {
+            let value_type = match member.r#type.clone().inner_type() {
+                NestableType::BasicType(t) => ValueType::BasicType(t),
+                NestableType::GenericString(t) => ValueType::GenericString(t),
+                NestableType::NamedType(t) => ValueType::Message(self.build(
+                    &format!("{}/msg/{}", msg.package, t),
+                    descriptions,
+                    visiting,
+                )?),
+                NestableType::NamespacedType(t) => {
+                    ValueType::Message(self.build(&t.to_string(), descriptions, visiting)?)
+                }
+            };
+            let r#type = match member.r#type {
+                MemberType::Array(ref t) => FieldType::Array {
+                    value_type,
+                    size: t.size,
+                },
+                MemberType::Sequence(_) => FieldType::Sequence { value_type },
+                MemberType::BoundedSequence(ref t) => FieldType::BoundedSequence {
+                    value_type,
+                    max_size: t.max_size,
+                },
+                _ => FieldType::Single(value_type),
+            };
+            let default = match member.default {
+                Some(ref default) => {
+                    parse_default(&r#type, default).ok_or_else(|| {
+                        DynamicError::InvalidDefault {
+                            path: format!("{}.{}", key, member.name),
+                            value: default.clone(),
+                        }
+                    })?;
+                    Some(&*Box::leak(default.clone().into_boxed_str()))
+                }
+                None => None,
+            };
+
+            let (field_size, field_align) = field_layout(&r#type);
+            let offset = align_up(size, field_align);
+            size = offset + field_size;
+            align = align.max(field_align);
+
+            fields.push(FieldDescription {
+                name: Box::leak(member.name.clone().into_boxed_str()),
+                r#type,
+                default,
+                offset,
+            });
+        }
+        if fields.is_empty() {
+            // `structure_needs_at_least_one_member`
+            size = 1;
+        }
+
+        let mut parts = key.splitn(3, '/').map(|s| &*Box::leak(s.into()));
+        let description: &'static MessageDescription = Box::leak(Box::new(MessageDescription {
+            package: parts.next().unwrap(),
+            namespace: parts.next().unwrap(),
+            name: parts.next().unwrap(),
+            size: align_up(size, align),
+            align,
+            fields: Box::leak(fields.into_boxed_slice()),
+        }));
+
+        visiting.pop();
+        descriptions.insert(key.into(), description);
+        Ok(description)
+    }
+}
+
+const fn align_up(offset: usize, align: usize) -> usize {
+    offset.div_ceil(align) * align
+}
+
+const fn value_layout(value_type: &ValueType) -> (usize, usize) {
+    match value_type {
+        ValueType::BasicType(t) => (t.size(), t.align()),
+        ValueType::GenericString(t) if t.is_wide() => {
+            (mem::size_of::<FFIWString>(), mem::align_of::<FFIWString>())
+        }
+        ValueType::GenericString(_) => (mem::size_of::<FFIString>(), mem::align_of::<FFIString>()),
+        ValueType::Message(t) => (t.size, t.align),
+    }
+}
+
+fn field_layout(field_type: &FieldType) -> (usize, usize) {
+    match field_type {
+        FieldType::Single(t) => value_layout(t),
+        FieldType::Array { value_type, size } => {
+            let (elem_size, elem_align) = value_layout(value_type);
+            (elem_size * size, elem_align)
+        }
+        FieldType::Sequence { .. } | FieldType::BoundedSequence { .. } => {
+            (mem::size_of::<FFISeq<u8>>(), mem::align_of::<FFISeq<u8>>())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rclrust_msg_types::*;
+
+    fn member(name: &str, r#type: MemberType) -> Member {
+        Member {
+            name: name.into(),
+            r#type,
+            default: None,
+        }
+    }
+
+    fn resolver() -> TypeResolver {
+        resolver_of(Distro::Foxy)
+    }
+
+    fn resolver_of(distro: Distro) -> TypeResolver {
+        let mut resolver = TypeResolver::with_distro(distro);
+        resolver.add_message(Message {
+            package: "builtin_interfaces".into(),
+            name: "Time".into(),
+            members: vec![
+                member("sec", BasicType::I32.into()),
+                member("nanosec", BasicType::U32.into()),
+            ],
+            constants: vec![],
+        });
+        resolver.add_message(Message {
+            package: "std_msgs".into(),
+            name: "Header".into(),
+            members: vec![
+                member(
+                    "stamp",
+                    NamespacedType {
+                        package: "builtin_interfaces".into(),
+                        namespace: "msg".into(),
+                        name: "Time".into(),
+                    }
+                    .into(),
+                ),
+                member("frame_id", GenericString::String.into()),
+            ],
+            constants: vec![],
+        });
+        resolver
+    }
+
+    #[test]
+    fn resolve_layout() {
+        let header = resolver().resolve("std_msgs/msg/Header").unwrap();
+        assert_eq!(header.fields[0].offset, 0);
+        assert_eq!(header.fields[1].offset, 8);
+        assert_eq!(header.size, 8 + mem::size_of::<FFIString>());
+        assert_eq!(header.align, mem::align_of::<FFIString>());
+    }
+
+    #[test]
+    fn resolve_short_name() {
+        let resolver = resolver();
+        let lhs = resolver.resolve("std_msgs/Header").unwrap();
+        let rhs = resolver.resolve("std_msgs/msg/Header").unwrap();
+        assert!(std::ptr::eq(lhs, rhs));
+    }
+
+    #[test]
+    fn resolve_unknown_type() {
+        assert_eq!(
+            resolver().resolve("std_msgs/msg/Bool"),
+            Err(DynamicError::UnknownType("std_msgs/msg/Bool".into()))
+        );
+    }
+
+    fn message(package: &str, name: &str, members: Vec<Member>) -> Message {
+        Message {
+            package: package.into(),
+            name: name.into(),
+            members,
+            constants: vec![],
+        }
+    }
+
+    fn add_interfaces(resolver: &mut TypeResolver) {
+        resolver.add_message(message(
+            "service_msgs",
+            "ServiceEventInfo",
+            vec![member("event_type", BasicType::U8.into())],
+        ));
+        resolver.add_message(message(
+            "unique_identifier_msgs",
+            "UUID",
+            vec![member(
+                "uuid",
+                Array {
+                    value_type: BasicType::U8.into(),
+                    size: 16,
+                }
+                .into(),
+            )],
+        ));
+        resolver.add_service(Service {
+            package: "test_msgs".into(),
+            name: "Trigger".into(),
+            request: message("test_msgs", "Trigger_Request", vec![]),
+            response: message(
+                "test_msgs",
+                "Trigger_Response",
+                vec![member("success", BasicType::Bool.into())],
+            ),
+        });
+        resolver.add_action(Action {
+            package: "test_msgs".into(),
+            name: "Count".into(),
+            goal: message(
+                "test_msgs",
+                "Count_Goal",
+                vec![member("to", BasicType::I32.into())],
+            ),
+            result: message("test_msgs", "Count_Result", vec![]),
+            feedback: message("test_msgs", "Count_Feedback", vec![]),
+        });
+    }
+
+    #[test]
+    fn resolve_service_events() {
+        let mut iron = resolver_of(Distro::Iron);
+        add_interfaces(&mut iron);
+        let event = iron.resolve("test_msgs/srv/Trigger_Event").unwrap();
+        assert_eq!(
+            event.fields.iter().map(|f| f.name).collect::<Vec<_>>(),
+            ["info", "request", "response"]
+        );
+        assert!(iron
+            .resolve("test_msgs/action/Count_SendGoal_Event")
+            .is_ok());
+        assert!(iron
+            .resolve("test_msgs/action/Count_GetResult_Event")
+            .is_ok());
+
+        let mut foxy = resolver();
+        add_interfaces(&mut foxy);
+        assert!(foxy.resolve("test_msgs/srv/Trigger_Response").is_ok());
+        assert_eq!(
+            foxy.resolve("test_msgs/srv/Trigger_Event"),
+            Err(DynamicError::UnknownType(
+                "test_msgs/srv/Trigger_Event".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn resolve_recursive_type() {
+        let mut resolver = TypeResolver::new();
+        resolver.add_message(Message {
+            package: "test_msgs".into(),
+            name: "Node".into(),
+            members: vec![member("children", NamedType("Node".into()).into())],
+            constants: vec![],
+        });
+        assert!(matches!(
+            resolver.resolve("test_msgs/msg/Node"),
+            Err(DynamicError::RecursiveType(_))
+        ));
+    }
+}
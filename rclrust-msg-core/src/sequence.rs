@@ -6,9 +6,9 @@ use crate::traits::{FFIFromRust, FFIToRust, ZeroInit};
 #[repr(C)]
 #[derive(Debug)]
 pub struct FFISeq<T> {
-    data: *mut T,
-    size: usize,
-    capacity: usize,
+    pub(crate) data: *mut T,
+    pub(crate) size: usize,
+    pub(crate) capacity: usize,
 }
 
 impl<T> FFISeq<T> {
@@ -59,6 +59,10 @@ impl<T> Deref for FFISeq<T> {
     type Target = [T];
 
     fn deref(&self) -> &[T] {
+        // rosidl leaves `data` null for empty sequences
+        if self.data.is_null() {
+            return &[];
+        }
         unsafe { std::slice::from_raw_parts(self.data, self.len()) }
     }
 }
@@ -79,7 +83,10 @@ pub struct OwnedFFISeq<T> {
 
 impl<T> OwnedFFISeq<T> {
     /// Extracts a slice.
-    pub fn as_slice(&self) -> &[T] {
+    pub const fn as_slice(&self) -> &[T] {
+        if self.data.is_null() {
+            return &[];
+        }
         unsafe { std::slice::from_raw_parts(self.data, self.len()) }
     }
 
@@ -132,7 +139,9 @@ where
 
 impl<T> Drop for OwnedFFISeq<T> {
     fn drop(&mut self) {
-        unsafe { Vec::from_raw_parts(self.data, self.size, self.capacity) };
+        if !self.data.is_null() {
+            unsafe { Vec::from_raw_parts(self.data, self.size, self.capacity) };
+        }
     }
 }
 
@@ -147,7 +156,10 @@ pub struct RefFFISeq<T> {
 
 impl<T> RefFFISeq<T> {
     /// Extracts a slice.
-    pub fn as_slice(&self) -> &[T] {
+    pub const fn as_slice(&self) -> &[T] {
+        if self.data.is_null() {
+            return &[];
+        }
         unsafe { std::slice::from_raw_parts(self.data, self.len()) }
     }
 
@@ -170,7 +182,7 @@ impl<T> FFIFromRust for RefFFISeq<T> {
             Self::zero_init()
         } else {
             Self {
-                data: vec.as_ptr() as *mut _,
+                data: vec.as_ptr().cast_mut(),
                 size: vec.len(),
                 capacity: vec.len(),
             }
@@ -9,9 +9,9 @@ use crate::traits::{FFIFromRust, FFIToRust, ZeroInit};
 #[repr(C)]
 #[derive(Debug)]
 pub struct FFIString {
-    data: *mut c_char,
-    size: usize,
-    capacity: usize,
+    pub(crate) data: *mut c_char,
+    pub(crate) size: usize,
+    pub(crate) capacity: usize,
 }
 
 impl FFIString {
@@ -97,8 +97,10 @@ impl FFIFromRust for OwnedFFIString {
 
 impl Drop for OwnedFFIString {
     fn drop(&mut self) {
-        unsafe {
-            CString::from_raw(self.data);
+        if !self.data.is_null() {
+            unsafe {
+                drop(CString::from_raw(self.data));
+            }
         }
     }
 }
@@ -107,9 +109,9 @@ impl Drop for OwnedFFIString {
 #[repr(C)]
 #[derive(Debug)]
 pub struct FFIWString {
-    data: *mut u16,
-    size: usize,
-    capacity: usize,
+    pub(crate) data: *mut u16,
+    pub(crate) size: usize,
+    pub(crate) capacity: usize,
 }
 
 impl FFIWString {
@@ -192,7 +194,9 @@ impl FFIFromRust for OwnedFFIWString {
 
 impl Drop for OwnedFFIWString {
     fn drop(&mut self) {
-        unsafe { U16CString::from_raw(self.data) };
+        if !self.data.is_null() {
+            unsafe { U16CString::from_raw(self.data) };
+        }
     }
 }
 
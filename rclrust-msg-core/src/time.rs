@@ -0,0 +1,145 @@
+//! Conversions of `builtin_interfaces/msg/Time` and `Duration`
+//!
+//! Both are `int32 sec` and `uint32 nanosec` in `[0, 1e9)`, so negative durations have negative
+//! seconds and positive nanoseconds. The generated types implement the standard conversions and
+//! operators with these functions.
+
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::TimeError;
+
+pub const NANOS_PER_SEC: i64 = 1_000_000_000;
+
+/// Returns the total nanoseconds of `sec` and `nanosec`.
+pub const fn to_nanos(sec: i32, nanosec: u32) -> i64 {
+    sec as i64 * NANOS_PER_SEC + nanosec as i64
+}
+
+/// Splits `nanos` into seconds and nanoseconds in `[0, 1e9)`.
+pub fn from_nanos(nanos: i64) -> Result<(i32, u32), TimeError> {
+    let sec = i32::try_from(nanos.div_euclid(NANOS_PER_SEC)).map_err(|_| TimeError::Overflow)?;
+    Ok((sec, nanos.rem_euclid(NANOS_PER_SEC) as u32))
+}
+
+pub fn from_std_duration(duration: Duration) -> Result<(i32, u32), TimeError> {
+    let sec = i32::try_from(duration.as_secs()).map_err(|_| TimeError::Overflow)?;
+    Ok((sec, duration.subsec_nanos()))
+}
+
+pub fn to_std_duration(sec: i32, nanosec: u32) -> Result<Duration, TimeError> {
+    let nanos = u64::try_from(to_nanos(sec, nanosec)).map_err(|_| TimeError::Negative)?;
+    Ok(Duration::from_nanos(nanos))
+}
+
+/// Returns the time since the UNIX epoch.
+pub fn from_system_time(time: SystemTime) -> Result<(i32, u32), TimeError> {
+    let since_epoch = time
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| TimeError::Negative)?;
+    from_std_duration(since_epoch)
+}
+
+pub fn to_system_time(sec: i32, nanosec: u32) -> SystemTime {
+    let nanos = to_nanos(sec, nanosec);
+    if nanos >= 0 {
+        UNIX_EPOCH + Duration::from_nanos(nanos as u64)
+    } else {
+        UNIX_EPOCH - Duration::from_nanos(nanos.unsigned_abs())
+    }
+}
+
+/// Conversions of `chrono` types
+#[cfg(feature = "chrono")]
+pub mod chrono {
+    use ::chrono::{DateTime, TimeDelta, Utc};
+
+    use super::*;
+
+    pub fn from_date_time(time: DateTime<Utc>) -> Result<(i32, u32), TimeError> {
+        if time.timestamp() < 0 {
+            return Err(TimeError::Negative);
+        }
+        let sec = i32::try_from(time.timestamp()).map_err(|_| TimeError::Overflow)?;
+        Ok((sec, time.timestamp_subsec_nanos()))
+    }
+
+    pub const fn to_date_time(sec: i32, nanosec: u32) -> DateTime<Utc> {
+        DateTime::from_timestamp_nanos(to_nanos(sec, nanosec))
+    }
+
+    pub fn from_time_delta(delta: TimeDelta) -> Result<(i32, u32), TimeError> {
+        from_nanos(delta.num_nanoseconds().ok_or(TimeError::Overflow)?)
+    }
+
+    pub const fn to_time_delta(sec: i32, nanosec: u32) -> TimeDelta {
+        TimeDelta::nanoseconds(to_nanos(sec, nanosec))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn nanos() {
+        assert_eq!(to_nanos(-2, 500_000_000), -1_500_000_000);
+        assert_eq!(from_nanos(-1_500_000_000), Ok((-2, 500_000_000)));
+        assert_eq!(from_nanos(1_500_000_000), Ok((1, 500_000_000)));
+        assert_eq!(
+            from_nanos(to_nanos(i32::MAX, 999_999_999)),
+            Ok((i32::MAX, 999_999_999))
+        );
+        assert_eq!(
+            from_nanos(to_nanos(i32::MAX, 999_999_999) + 1),
+            Err(TimeError::Overflow)
+        );
+        assert_eq!(from_nanos(to_nanos(i32::MIN, 0)), Ok((i32::MIN, 0)));
+        assert_eq!(
+            from_nanos(to_nanos(i32::MIN, 0) - 1),
+            Err(TimeError::Overflow)
+        );
+    }
+
+    #[test]
+    fn std_duration() {
+        let duration = Duration::new(3, 250);
+        assert_eq!(from_std_duration(duration), Ok((3, 250)));
+        assert_eq!(to_std_duration(3, 250), Ok(duration));
+        assert_eq!(to_std_duration(-1, 999_999_999), Err(TimeError::Negative));
+        assert_eq!(
+            from_std_duration(Duration::from_secs(1 << 31)),
+            Err(TimeError::Overflow)
+        );
+    }
+
+    #[test]
+    fn system_time() {
+        let time = UNIX_EPOCH + Duration::new(1_700_000_000, 42);
+        assert_eq!(from_system_time(time), Ok((1_700_000_000, 42)));
+        assert_eq!(to_system_time(1_700_000_000, 42), time);
+        assert_eq!(to_system_time(-1, 0), UNIX_EPOCH - Duration::from_secs(1));
+        assert_eq!(
+            from_system_time(UNIX_EPOCH - Duration::from_secs(1)),
+            Err(TimeError::Negative)
+        );
+        assert_eq!(
+            from_system_time(UNIX_EPOCH + Duration::from_secs(1 << 31)),
+            Err(TimeError::Overflow)
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono() {
+        use ::chrono::{DateTime, TimeDelta};
+
+        let time = DateTime::from_timestamp(1_700_000_000, 42).unwrap();
+        assert_eq!(super::chrono::from_date_time(time), Ok((1_700_000_000, 42)));
+        assert_eq!(super::chrono::to_date_time(1_700_000_000, 42), time);
+
+        let delta = TimeDelta::milliseconds(-1_500);
+        assert_eq!(super::chrono::from_time_delta(delta), Ok((-2, 500_000_000)));
+        assert_eq!(super::chrono::to_time_delta(-2, 500_000_000), delta);
+    }
+}
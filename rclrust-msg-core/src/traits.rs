@@ -1,28 +1,129 @@
+use std::convert::TryInto;
 use std::os::raw::c_void;
 use widestring::U16String;
 
+use crate::action::GoalId;
+use crate::dynamic::Value;
+use crate::introspection::MessageDescription;
+
 pub trait MessageT: Default {
     type Raw: RawMessageT;
     type RawRef: RawMessageRefT;
 
+    /// The package of the type, e.g. `std_msgs`
+    const PACKAGE: &'static str;
+    /// The namespace of the type, i.e. `msg`, or `srv` and `action` for the messages of services
+    /// and actions
+    const NAMESPACE: &'static str;
+    /// The name of the type, e.g. `String`
+    const NAME: &'static str;
+    /// The full name of the type, e.g. `std_msgs/msg/String`
+    const TYPE_NAME: &'static str;
+
     fn type_support() -> *const c_void;
 
+    /// Returns the `rosidl_typesupport_introspection_c` handle of the message.
+    fn introspection_type_support() -> *const c_void;
+
+    /// # Safety
+    ///
+    /// `from` must be a message initialized by the rosidl C runtime.
     unsafe fn from_raw(from: &Self::Raw) -> Self;
 
+    /// # Safety
+    ///
+    /// The returned value borrows buffers owned by `self`, so it must not outlive `self`.
     unsafe fn to_raw_ref(&self) -> Self::RawRef;
 }
 
+pub trait MessageIntrospection {
+    const DESCRIPTION: &'static MessageDescription;
+
+    fn description() -> &'static MessageDescription {
+        Self::DESCRIPTION
+    }
+}
+
+/// Conversion between Rust values and [`Value`]s of dynamic messages
+pub trait DynamicValue: Sized {
+    fn to_value(&self) -> Value;
+
+    /// Returns `None` if `value` does not have the type of `Self`.
+    fn from_value(value: &Value) -> Option<Self>;
+}
+
 pub trait RawMessageT: FFIToRust + Default {}
 
 pub trait RawMessageRefT: FFIFromRust {}
 
+/// A message of basic types, arrays of them and other plain messages, whose C structure has no
+/// pointers and can be shared without copying
+///
+/// # Safety
+///
+/// `Self` must have the layout of `Self::Raw`, and [`PlainMessage::HAS_PADDING`] must be `true`
+/// if `Self::Raw` has any padding bytes.
+pub unsafe trait PlainMessage: MessageT {
+    /// Whether the C structure has padding bytes, which are not initialized
+    const HAS_PADDING: bool;
+
+    /// Returns the C structure of the message without copying.
+    fn as_raw(&self) -> &Self::Raw {
+        unsafe { &*(self as *const Self as *const Self::Raw) }
+    }
+
+    /// Returns the C structure of the message without copying.
+    fn as_raw_mut(&mut self) -> &mut Self::Raw {
+        unsafe { &mut *(self as *mut Self as *mut Self::Raw) }
+    }
+
+    /// Returns the message of the C structure without copying.
+    fn from_raw_ref(raw: &Self::Raw) -> &Self {
+        unsafe { &*(raw as *const Self::Raw as *const Self) }
+    }
+
+    /// Returns the message of the C structure without copying.
+    fn from_raw_mut(raw: &mut Self::Raw) -> &mut Self {
+        unsafe { &mut *(raw as *mut Self::Raw as *mut Self) }
+    }
+
+    /// Returns the bytes of the C structure, or `None` if it has padding bytes.
+    fn as_bytes(&self) -> Option<&[u8]> {
+        if Self::HAS_PADDING {
+            return None;
+        }
+        Some(unsafe {
+            std::slice::from_raw_parts(
+                self as *const Self as *const u8,
+                std::mem::size_of::<Self>(),
+            )
+        })
+    }
+}
+
 pub trait ServiceT {
     type Request: MessageT;
     type Response: MessageT;
 
+    /// The package of the type, e.g. `std_srvs`
+    const PACKAGE: &'static str;
+    /// The namespace of the type, i.e. `srv`, or `action` for the services of actions
+    const NAMESPACE: &'static str;
+    /// The name of the type, e.g. `SetBool`
+    const NAME: &'static str;
+    /// The full name of the type, e.g. `std_srvs/srv/SetBool`
+    const TYPE_NAME: &'static str;
+
     fn type_support() -> *const c_void;
 }
 
+/// A service whose events are published, i.e. of Iron or later
+pub trait ServiceEventT: ServiceT {
+    /// `<Service>_Event`, which carries `service_msgs/msg/ServiceEventInfo` and the request or
+    /// the response
+    type Event: MessageT;
+}
+
 pub trait ActionT {
     type Goal: MessageT;
     type Result: MessageT;
@@ -31,7 +132,41 @@ pub trait ActionT {
     type GetResult: ServiceT;
     type FeedbackMessage: MessageT;
 
+    /// The package of the type, e.g. `example_interfaces`
+    const PACKAGE: &'static str;
+    /// The namespace of the type, i.e. `action`
+    const NAMESPACE: &'static str;
+    /// The name of the type, e.g. `Fibonacci`
+    const NAME: &'static str;
+    /// The full name of the type, e.g. `example_interfaces/action/Fibonacci`
+    const TYPE_NAME: &'static str;
+
     fn type_support() -> *const c_void;
+
+    /// Creates the request of `SendGoal` for `goal`.
+    fn new_send_goal_request(
+        goal_id: GoalId,
+        goal: Self::Goal,
+    ) -> <Self::SendGoal as ServiceT>::Request;
+
+    /// Splits the request of `SendGoal` into the goal ID and the goal.
+    fn split_send_goal_request(
+        request: <Self::SendGoal as ServiceT>::Request,
+    ) -> (GoalId, Self::Goal);
+
+    /// Creates the request of `GetResult` for the goal of `goal_id`.
+    fn new_get_result_request(goal_id: GoalId) -> <Self::GetResult as ServiceT>::Request;
+
+    /// Splits the response of `GetResult` into the status and the result.
+    fn split_get_result_response(
+        response: <Self::GetResult as ServiceT>::Response,
+    ) -> (i8, Self::Result);
+
+    /// Creates the message publishing `feedback` of the goal of `goal_id`.
+    fn new_feedback_message(goal_id: GoalId, feedback: Self::Feedback) -> Self::FeedbackMessage;
+
+    /// Splits the feedback message into the goal ID and the feedback.
+    fn split_feedback_message(message: Self::FeedbackMessage) -> (GoalId, Self::Feedback);
 }
 
 pub trait ZeroInit {
@@ -56,9 +191,69 @@ impl<T> ZeroInit for Vec<T> {
     }
 }
 
+impl DynamicValue for String {
+    fn to_value(&self) -> Value {
+        Value::String(self.clone())
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(v) => Some(v.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl DynamicValue for U16String {
+    fn to_value(&self) -> Value {
+        Value::WString(self.clone())
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::WString(v) => Some(v.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl<T: DynamicValue> DynamicValue for Vec<T> {
+    fn to_value(&self) -> Value {
+        Value::Sequence(self.iter().map(T::to_value).collect())
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Sequence(v) => v.iter().map(T::from_value).collect(),
+            _ => None,
+        }
+    }
+}
+
+impl<T: DynamicValue, const N: usize> DynamicValue for [T; N] {
+    fn to_value(&self) -> Value {
+        Value::Array(self.iter().map(T::to_value).collect())
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Array(v) => v
+                .iter()
+                .map(T::from_value)
+                .collect::<Option<Vec<_>>>()?
+                .try_into()
+                .ok(),
+            _ => None,
+        }
+    }
+}
+
 pub trait FFIToRust {
     type Target;
 
+    /// # Safety
+    ///
+    /// Pointers held by `self` must be valid.
     unsafe fn to_rust(&self) -> Self::Target;
 }
 
@@ -69,7 +264,7 @@ pub trait FFIFromRust {
 }
 
 macro_rules! impl_traits_to_primitive {
-    ($type: ty) => {
+    ($type: ty, $variant: ident) => {
         impl ZeroInit for $type {
             fn zero_init() -> Self {
                 Self::default()
@@ -83,17 +278,30 @@ macro_rules! impl_traits_to_primitive {
                 *self
             }
         }
+
+        impl DynamicValue for $type {
+            fn to_value(&self) -> Value {
+                Value::$variant(*self)
+            }
+
+            fn from_value(value: &Value) -> Option<Self> {
+                match value {
+                    Value::$variant(v) => Some(*v),
+                    _ => None,
+                }
+            }
+        }
     };
 }
 
-impl_traits_to_primitive!(i8);
-impl_traits_to_primitive!(i16);
-impl_traits_to_primitive!(i32);
-impl_traits_to_primitive!(i64);
-impl_traits_to_primitive!(u8);
-impl_traits_to_primitive!(u16);
-impl_traits_to_primitive!(u32);
-impl_traits_to_primitive!(u64);
-impl_traits_to_primitive!(f32);
-impl_traits_to_primitive!(f64);
-impl_traits_to_primitive!(bool);
+impl_traits_to_primitive!(i8, I8);
+impl_traits_to_primitive!(i16, I16);
+impl_traits_to_primitive!(i32, I32);
+impl_traits_to_primitive!(i64, I64);
+impl_traits_to_primitive!(u8, U8);
+impl_traits_to_primitive!(u16, U16);
+impl_traits_to_primitive!(u32, U32);
+impl_traits_to_primitive!(u64, U64);
+impl_traits_to_primitive!(f32, F32);
+impl_traits_to_primitive!(f64, F64);
+impl_traits_to_primitive!(bool, Bool);
@@ -0,0 +1,967 @@
+//! Conversion of messages from and to YAML in the same syntax as `ros2 topic pub` and `ros2 topic echo`
+
+use std::convert::TryInto;
+use std::fmt::Write as _;
+
+use widestring::U16String;
+
+use crate::dynamic::{DynamicMessage, Value};
+use crate::error::DynamicError;
+use crate::introspection::{BasicType, FieldType, MessageDescription, ValueType};
+use crate::traits::DynamicValue;
+
+/// Options corresponding to the flags of `ros2 topic echo`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct YamlOptions {
+    /// `--truncate-length`: the maximum length of strings, arrays and sequences
+    pub truncate_length: Option<usize>,
+    /// `--no-arr`: prints the type and length instead of the contents of arrays and sequences
+    pub no_arr: bool,
+}
+
+/// Renders `msg` as YAML, without the `---` separator printed between messages.
+pub fn to_yaml(msg: &DynamicMessage, options: &YamlOptions) -> String {
+    let mut out = String::new();
+    match message_node(msg, options) {
+        Node::Map(entries) if !entries.is_empty() => write_map(&mut out, &entries, 0, false),
+        node => {
+            out.push_str(&flow(&node));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// The YAML document built before being written out
+enum Node {
+    Scalar(String),
+    Seq(Vec<Self>),
+    Map(Vec<(&'static str, Self)>),
+}
+
+impl Node {
+    /// Whether the node is written in the flow style, i.e. on a single line
+    fn is_flow(&self) -> bool {
+        match self {
+            Self::Scalar(_) => true,
+            Self::Seq(items) => items.iter().all(|item| matches!(item, Self::Scalar(_))),
+            Self::Map(entries) => entries.is_empty(),
+        }
+    }
+}
+
+fn message_node(msg: &DynamicMessage, options: &YamlOptions) -> Node {
+    Node::Map(
+        msg.fields()
+            .map(|(field, value)| (field.name, field_node(&field.r#type, value, options)))
+            .collect(),
+    )
+}
+
+/// Abbreviates arrays and sequences like `__abbreviate_array_info` of `rosidl_runtime_py` with
+/// `--no-arr`.
+fn field_node(field_type: &FieldType, value: &Value, options: &YamlOptions) -> Node {
+    if !options.no_arr {
+        return value_node(value, options, false);
+    }
+    let info = match (field_type, value) {
+        (FieldType::Array { value_type, size }, Value::Array(_)) => {
+            format!("<array type: {}[{}]>", idl_type_name(value_type), size)
+        }
+        (FieldType::Sequence { value_type }, Value::Sequence(items)) => format!(
+            "<sequence type: {}, length: {}>",
+            idl_type_name(value_type),
+            items.len()
+        ),
+        (
+            FieldType::BoundedSequence {
+                value_type,
+                max_size,
+            },
+            Value::Sequence(items),
+        ) => format!(
+            "<sequence type: {}[{}], length: {}>",
+            idl_type_name(value_type),
+            max_size,
+            items.len()
+        ),
+        _ => return value_node(value, options, false),
+    };
+    Node::Scalar(quote(&info, true))
+}
+
+/// Returns the IDL name of basic types and the full name of messages, e.g. `std_msgs/msg/Empty`.
+fn idl_type_name(value_type: &ValueType) -> String {
+    match value_type {
+        ValueType::BasicType(t) => match t {
+            BasicType::I8 => "int8",
+            BasicType::I16 => "int16",
+            BasicType::I32 => "int32",
+            BasicType::I64 => "int64",
+            BasicType::U8 | BasicType::Char => "uint8",
+            BasicType::U16 => "uint16",
+            BasicType::U32 => "uint32",
+            BasicType::U64 => "uint64",
+            BasicType::F32 => "float",
+            BasicType::F64 => "double",
+            BasicType::Bool => "boolean",
+            BasicType::Byte => "octet",
+        }
+        .into(),
+        ValueType::GenericString(t) if t.is_wide() => "wstring".into(),
+        ValueType::GenericString(_) => "string".into(),
+        ValueType::Message(t) => t.to_string(),
+    }
+}
+
+/// `in_flow` is true for elements of arrays and sequences, which may be written in the flow style.
+fn value_node(value: &Value, options: &YamlOptions, in_flow: bool) -> Node {
+    match value {
+        Value::Bool(v) => Node::Scalar(v.to_string()),
+        Value::I8(v) => Node::Scalar(v.to_string()),
+        Value::I16(v) => Node::Scalar(v.to_string()),
+        Value::I32(v) => Node::Scalar(v.to_string()),
+        Value::I64(v) => Node::Scalar(v.to_string()),
+        Value::U8(v) => Node::Scalar(v.to_string()),
+        Value::U16(v) => Node::Scalar(v.to_string()),
+        Value::U32(v) => Node::Scalar(v.to_string()),
+        Value::U64(v) => Node::Scalar(v.to_string()),
+        Value::F32(v) => Node::Scalar(format_float((*v).into())),
+        Value::F64(v) => Node::Scalar(format_float(*v)),
+        Value::String(v) => string_node(v, options, in_flow),
+        Value::WString(v) => string_node(&v.to_string_lossy(), options, in_flow),
+        Value::Message(v) => message_node(v, options),
+        Value::Array(items) | Value::Sequence(items) => {
+            let mut nodes = items
+                .iter()
+                .take(options.truncate_length.unwrap_or(usize::MAX))
+                .map(|item| value_node(item, options, true))
+                .collect::<Vec<_>>();
+            if nodes.len() < items.len() {
+                nodes.push(Node::Scalar(quote("...", true)));
+            }
+            Node::Seq(nodes)
+        }
+    }
+}
+
+fn string_node(s: &str, options: &YamlOptions, in_flow: bool) -> Node {
+    match options.truncate_length {
+        Some(len) if s.chars().count() > len => {
+            let truncated = s.chars().take(len).collect::<String>() + "...";
+            Node::Scalar(quote(&truncated, in_flow))
+        }
+        _ => Node::Scalar(quote(s, in_flow)),
+    }
+}
+
+fn write_map(out: &mut String, entries: &[(&str, Node)], indent: usize, inline_first: bool) {
+    for (i, (key, node)) in entries.iter().enumerate() {
+        if i > 0 || !inline_first {
+            out.push_str(&" ".repeat(indent));
+        }
+        out.push_str(key);
+        out.push(':');
+        if node.is_flow() {
+            out.push(' ');
+            out.push_str(&flow(node));
+            out.push('\n');
+        } else {
+            out.push('\n');
+            match node {
+                Node::Map(entries) => write_map(out, entries, indent + 2, false),
+                // Sequences in mappings are not indented, as PyYAML does.
+                Node::Seq(items) => write_seq(out, items, indent),
+                Node::Scalar(_) => unreachable!(),
+            }
+        }
+    }
+}
+
+fn write_seq(out: &mut String, items: &[Node], indent: usize) {
+    for item in items {
+        out.push_str(&" ".repeat(indent));
+        out.push_str("- ");
+        match item {
+            Node::Map(entries) if !entries.is_empty() => write_map(out, entries, indent + 2, true),
+            node => {
+                out.push_str(&flow(node));
+                out.push('\n');
+            }
+        }
+    }
+}
+
+fn flow(node: &Node) -> String {
+    match node {
+        Node::Scalar(s) => s.clone(),
+        Node::Seq(items) => format!(
+            "[{}]",
+            items.iter().map(flow).collect::<Vec<_>>().join(", ")
+        ),
+        Node::Map(entries) => format!(
+            "{{{}}}",
+            entries
+                .iter()
+                .map(|(key, node)| format!("{}: {}", key, flow(node)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// Formats a float in the same way as `repr()` of Python and PyYAML.
+fn format_float(v: f64) -> String {
+    if v.is_nan() {
+        return ".nan".into();
+    }
+    if v.is_infinite() {
+        return if v > 0. { ".inf" } else { "-.inf" }.into();
+    }
+
+    let abs = v.abs();
+    if abs != 0. && !(1e-4..1e16).contains(&abs) {
+        let s = format!("{:e}", v);
+        let (mantissa, exponent) = s.split_once('e').unwrap();
+        let exponent = exponent.parse::<i32>().unwrap();
+        let dot = if mantissa.contains('.') { "" } else { ".0" };
+        let sign = if exponent < 0 { '-' } else { '+' };
+        format!("{}{}e{}{:02}", mantissa, dot, sign, exponent.abs())
+    } else {
+        let s = v.to_string();
+        if s.contains('.') {
+            s
+        } else {
+            s + ".0"
+        }
+    }
+}
+
+/// Quotes `s` if it can not be written as a plain scalar.
+///
+/// `in_flow` must be true for scalars used in flow collections.
+fn quote(s: &str, in_flow: bool) -> String {
+    if s.chars().any(|c| c.is_control()) {
+        let mut quoted = String::from('"');
+        for c in s.chars() {
+            match c {
+                '"' => quoted.push_str("\\\""),
+                '\\' => quoted.push_str("\\\\"),
+                '\n' => quoted.push_str("\\n"),
+                '\t' => quoted.push_str("\\t"),
+                '\r' => quoted.push_str("\\r"),
+                c if c.is_control() => write!(quoted, "\\x{:02X}", c as u32).unwrap(),
+                c => quoted.push(c),
+            }
+        }
+        quoted.push('"');
+        return quoted;
+    }
+
+    if needs_quotes(s, in_flow) {
+        format!("'{}'", s.replace('\'', "''"))
+    } else {
+        s.into()
+    }
+}
+
+fn needs_quotes(s: &str, in_flow: bool) -> bool {
+    let first = match s.chars().next() {
+        Some(c) => c,
+        None => return true,
+    };
+    let second = s.chars().nth(1);
+
+    s.starts_with(' ')
+        || s.starts_with("---")
+        || s.starts_with("...")
+        || s.ends_with(' ')
+        || s.ends_with(':')
+        || "[]{},#&*!|>'\"%@`".contains(first)
+        || ("-?:".contains(first) && second.is_none_or(|c| c == ' '))
+        || s.contains(": ")
+        || s.contains(" #")
+        || (in_flow && s.contains(|c| "[]{},".contains(c)))
+        || is_implicit(s)
+}
+
+/// Whether `s` would be read back as a value other than a string by YAML 1.1.
+fn is_implicit(s: &str) -> bool {
+    const KEYWORDS: &[&str] = &[
+        "~", "null", "Null", "NULL", "yes", "Yes", "YES", "no", "No", "NO", "true", "True", "TRUE",
+        "false", "False", "FALSE", "on", "On", "ON", "off", "Off", "OFF", ".inf", ".Inf", ".INF",
+        "-.inf", "-.Inf", "-.INF", "+.inf", "+.Inf", "+.INF", ".nan", ".NaN", ".NAN", "=", "<<",
+    ];
+    if KEYWORDS.contains(&s) {
+        return true;
+    }
+
+    let digits = s.trim_start_matches(['+', '-']).replace('_', "");
+    let radix = [("0x", 16), ("0b", 2), ("0o", 8)]
+        .iter()
+        .find_map(|&(prefix, radix)| digits.strip_prefix(prefix).map(|d| (d, radix)));
+    match radix {
+        Some((d, radix)) => !d.is_empty() && u64::from_str_radix(d, radix).is_ok(),
+        None => {
+            digits.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+                && digits.parse::<f64>().is_ok()
+        }
+    }
+}
+
+/// Parses `s` in the syntax of `ros2 topic pub`, e.g. `{linear: {x: 1.0}, angular: {z: 0.5}}`,
+/// as a message of `description`.
+///
+/// Block mappings and sequences such as the output of [`to_yaml`] are also accepted.
+///
+/// Unspecified fields have the default values.
+pub fn parse_yaml(
+    description: &'static MessageDescription,
+    s: &str,
+) -> Result<DynamicMessage, DynamicError> {
+    let mut msg = DynamicMessage::new(description)?;
+    apply_yaml(&mut msg, s)?;
+    Ok(msg)
+}
+
+/// Overwrites the fields of `msg` specified in `s`.
+pub fn apply_yaml(msg: &mut DynamicMessage, s: &str) -> Result<(), DynamicError> {
+    let entries = Parser { s, pos: 0 }.parse_document()?;
+    apply_entries(msg, entries, "")
+}
+
+/// Parses `s` as a generated message.
+///
+/// Unspecified fields keep the values of `T::default()`.
+pub fn from_yaml<T: DynamicValue + Default>(s: &str) -> Result<T, DynamicError> {
+    let mut msg = match T::default().to_value() {
+        Value::Message(msg) => msg,
+        _ => {
+            return Err(DynamicError::UnsupportedType(
+                std::any::type_name::<T>().into(),
+            ))
+        }
+    };
+    apply_yaml(&mut msg, s)?;
+    let description = msg.description();
+    T::from_value(&Value::Message(msg)).ok_or_else(|| DynamicError::TypeMismatch {
+        path: String::new(),
+        expected: description.to_string(),
+    })
+}
+
+/// A parsed YAML node
+#[derive(Debug, PartialEq)]
+enum Yaml {
+    Scalar { text: String, quoted: bool },
+    Seq(Vec<Self>),
+    Map(Vec<(String, Self)>),
+}
+
+struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error<T>(&self, message: &str) -> Result<T, DynamicError> {
+        Err(DynamicError::YamlParseError(format!(
+            "{} at position {}",
+            message, self.pos
+        )))
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.s[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn column(&self) -> usize {
+        self.pos - self.s[..self.pos].rfind('\n').map_or(0, |i| i + 1)
+    }
+
+    /// Skips spaces and comments, and also line breaks if `newlines` is true.
+    fn skip_spaces(&mut self, newlines: bool) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start_matches(|c| {
+                c == ' ' || c == '\t' || (newlines && (c == '\n' || c == '\r'))
+            });
+            self.pos += rest.len() - trimmed.len();
+            let after_space = self.pos == 0 || self.s[..self.pos].ends_with(char::is_whitespace);
+            if trimmed.starts_with('#') && after_space {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn at_line_end(&mut self) -> bool {
+        self.skip_spaces(false);
+        matches!(self.peek(), None | Some('\n') | Some('\r'))
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), DynamicError> {
+        self.skip_spaces(true);
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            Ok(())
+        } else {
+            self.error(&format!("expected '{}'", c))
+        }
+    }
+
+    /// Parses a mapping in either the flow or the block style.
+    fn parse_document(&mut self) -> Result<Vec<(String, Yaml)>, DynamicError> {
+        self.skip_spaces(true);
+        let entries = match self.peek() {
+            None => Vec::new(),
+            Some('{') => match self.parse_flow_node()? {
+                Yaml::Map(entries) => entries,
+                _ => unreachable!(),
+            },
+            Some(_) => self.parse_block_map(self.column())?,
+        };
+        self.skip_spaces(true);
+        if self.peek().is_some() {
+            return self.error("unexpected trailing characters");
+        }
+        Ok(entries)
+    }
+
+    /// Whether a `key:` starts at the current position
+    fn at_key(&mut self) -> bool {
+        let pos = self.pos;
+        let is_key = self.parse_scalar(false).is_ok()
+            && self.peek() == Some(':')
+            && self.s[self.pos + 1..]
+                .chars()
+                .next()
+                .is_none_or(char::is_whitespace);
+        self.pos = pos;
+        is_key
+    }
+
+    fn parse_key(&mut self, flow: bool) -> Result<String, DynamicError> {
+        match self.parse_scalar(flow)? {
+            Yaml::Scalar { text, .. } if !text.is_empty() => {
+                self.expect(':')?;
+                Ok(text)
+            }
+            _ => self.error("expected a key"),
+        }
+    }
+
+    fn parse_block_map(&mut self, indent: usize) -> Result<Vec<(String, Yaml)>, DynamicError> {
+        let mut entries = Vec::new();
+        loop {
+            let key = self.parse_key(false)?;
+            let value = if self.at_line_end() {
+                self.skip_spaces(true);
+                let column = self.column();
+                if self.peek().is_none() || column < indent {
+                    Yaml::Scalar {
+                        text: String::new(),
+                        quoted: false,
+                    }
+                } else if self.rest().starts_with('-') && column >= indent {
+                    self.parse_block_seq(column)?
+                } else if column > indent {
+                    Yaml::Map(self.parse_block_map(column)?)
+                } else {
+                    Yaml::Scalar {
+                        text: String::new(),
+                        quoted: false,
+                    }
+                }
+            } else {
+                let value = self.parse_node(false)?;
+                if !self.at_line_end() {
+                    return self.error("expected a new line");
+                }
+                value
+            };
+            entries.push((key, value));
+
+            self.skip_spaces(true);
+            match self.column() {
+                _ if self.peek().is_none() => break,
+                column if column == indent && !self.rest().starts_with('-') => {}
+                column if column <= indent => break,
+                _ => return self.error("invalid indentation"),
+            }
+        }
+        Ok(entries)
+    }
+
+    fn parse_block_seq(&mut self, indent: usize) -> Result<Yaml, DynamicError> {
+        let mut items = Vec::new();
+        while self.column() == indent && self.rest().starts_with('-') {
+            self.pos += 1;
+            if self.at_line_end() {
+                return self.error("expected a value");
+            }
+            let item = if self.at_key() {
+                Yaml::Map(self.parse_block_map(self.column())?)
+            } else {
+                let item = self.parse_node(false)?;
+                if !self.at_line_end() {
+                    return self.error("expected a new line");
+                }
+                item
+            };
+            items.push(item);
+            self.skip_spaces(true);
+        }
+        Ok(Yaml::Seq(items))
+    }
+
+    fn parse_node(&mut self, flow: bool) -> Result<Yaml, DynamicError> {
+        match self.peek() {
+            Some('{') | Some('[') => self.parse_flow_node(),
+            _ => self.parse_scalar(flow),
+        }
+    }
+
+    fn parse_flow_node(&mut self) -> Result<Yaml, DynamicError> {
+        self.skip_spaces(true);
+        let (is_map, close) = match self.peek() {
+            Some('{') => (true, '}'),
+            Some('[') => (false, ']'),
+            Some(_) => return self.parse_scalar(true),
+            None => return self.error("unexpected end of input"),
+        };
+        self.pos += 1;
+
+        let mut entries = Vec::new();
+        let mut items = Vec::new();
+        self.skip_spaces(true);
+        if self.peek() != Some(close) {
+            loop {
+                self.skip_spaces(true);
+                if is_map {
+                    let key = self.parse_key(true)?;
+                    self.skip_spaces(true);
+                    let value = match self.peek() {
+                        Some(',') | Some('}') => Yaml::Scalar {
+                            text: String::new(),
+                            quoted: false,
+                        },
+                        _ => self.parse_flow_node()?,
+                    };
+                    entries.push((key, value));
+                } else {
+                    items.push(self.parse_flow_node()?);
+                }
+                self.skip_spaces(true);
+                match self.peek() {
+                    Some(',') => self.pos += 1,
+                    Some(c) if c == close => break,
+                    _ => return self.error(&format!("expected ',' or '{}'", close)),
+                }
+            }
+        }
+        self.pos += 1;
+        Ok(if is_map {
+            Yaml::Map(entries)
+        } else {
+            Yaml::Seq(items)
+        })
+    }
+
+    fn parse_scalar(&mut self, flow: bool) -> Result<Yaml, DynamicError> {
+        match self.peek() {
+            Some('\'') => {
+                self.pos += 1;
+                let mut text = String::new();
+                loop {
+                    match self.rest().find('\'') {
+                        Some(end) => {
+                            text.push_str(&self.rest()[..end]);
+                            self.pos += end + 1;
+                            if self.peek() == Some('\'') {
+                                text.push('\'');
+                                self.pos += 1;
+                            } else {
+                                break;
+                            }
+                        }
+                        None => return self.error("unterminated string"),
+                    }
+                }
+                Ok(Yaml::Scalar { text, quoted: true })
+            }
+            Some('"') => {
+                self.pos += 1;
+                let mut text = String::new();
+                let mut chars = self.rest().char_indices();
+                loop {
+                    match chars.next() {
+                        Some((i, '"')) => {
+                            self.pos += i + 1;
+                            break;
+                        }
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, 'n')) => text.push('\n'),
+                            Some((_, 't')) => text.push('\t'),
+                            Some((_, 'r')) => text.push('\r'),
+                            Some((_, '0')) => text.push('\0'),
+                            Some((_, c @ ('"' | '\\' | '/' | '\''))) => text.push(c),
+                            _ => return self.error("invalid escape sequence"),
+                        },
+                        Some((_, c)) => text.push(c),
+                        None => return self.error("unterminated string"),
+                    }
+                }
+                Ok(Yaml::Scalar { text, quoted: true })
+            }
+            _ => {
+                let rest = self.rest();
+                let end = rest
+                    .char_indices()
+                    .find(|&(i, c)| {
+                        let next = rest[i + c.len_utf8()..].chars().next();
+                        c == '\n'
+                            || c == '\r'
+                            || (flow && ",[]{}".contains(c))
+                            || (c == ':'
+                                && next.is_none_or(|n| {
+                                    n.is_whitespace() || (flow && ",[]{}".contains(n))
+                                }))
+                            || (c == '#' && rest[..i].ends_with([' ', '\t']))
+                    })
+                    .map_or(rest.len(), |(i, _)| i);
+                self.pos += end;
+                Ok(Yaml::Scalar {
+                    text: rest[..end].trim_end().into(),
+                    quoted: false,
+                })
+            }
+        }
+    }
+}
+
+fn join_path(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.into()
+    } else {
+        format!("{}.{}", prefix, name)
+    }
+}
+
+fn apply_entries(
+    msg: &mut DynamicMessage,
+    entries: Vec<(String, Yaml)>,
+    prefix: &str,
+) -> Result<(), DynamicError> {
+    let description = msg.description();
+    for (key, node) in entries {
+        let path = join_path(prefix, &key);
+        let field = description
+            .field(&key)
+            .ok_or_else(|| DynamicError::UnknownField(path.clone()))?;
+        let value = field_value(node, &field.r#type, msg.get(&key)?, &path)?;
+        msg.set(&key, value).map_err(|e| match e {
+            DynamicError::TypeMismatch { expected, .. } => DynamicError::TypeMismatch {
+                path: path.clone(),
+                expected,
+            },
+            e => e,
+        })?;
+    }
+    Ok(())
+}
+
+fn field_value(
+    node: Yaml,
+    field_type: &FieldType,
+    current: &Value,
+    path: &str,
+) -> Result<Value, DynamicError> {
+    let mismatch = || DynamicError::TypeMismatch {
+        path: path.into(),
+        expected: field_type.to_string(),
+    };
+    let elements = |items: Vec<Yaml>, value_type: &ValueType| {
+        let zero = Value::zero(value_type)?;
+        items
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let current = current.as_slice().and_then(|v| v.get(i)).unwrap_or(&zero);
+                scalar_or_message(item, value_type, current, &format!("{}[{}]", path, i))
+            })
+            .collect::<Result<Vec<_>, _>>()
+    };
+
+    match (field_type, node) {
+        (FieldType::Single(t), node) => scalar_or_message(node, t, current, path),
+        (FieldType::Array { value_type, size }, Yaml::Seq(items)) if items.len() == *size => {
+            elements(items, value_type).map(Value::Array)
+        }
+        (FieldType::Sequence { value_type }, Yaml::Seq(items)) => {
+            elements(items, value_type).map(Value::Sequence)
+        }
+        (
+            FieldType::BoundedSequence {
+                value_type,
+                max_size,
+            },
+            Yaml::Seq(items),
+        ) if items.len() <= *max_size => elements(items, value_type).map(Value::Sequence),
+        _ => Err(mismatch()),
+    }
+}
+
+fn scalar_or_message(
+    node: Yaml,
+    value_type: &ValueType,
+    current: &Value,
+    path: &str,
+) -> Result<Value, DynamicError> {
+    let mismatch = || DynamicError::TypeMismatch {
+        path: path.into(),
+        expected: value_type.to_string(),
+    };
+
+    match (value_type, node) {
+        (ValueType::Message(t), Yaml::Map(entries)) => {
+            let mut msg = match current.as_message() {
+                Some(msg) => msg.clone(),
+                None => DynamicMessage::new(t)?,
+            };
+            apply_entries(&mut msg, entries, path)?;
+            Ok(Value::Message(msg))
+        }
+        (ValueType::GenericString(t), Yaml::Scalar { text, .. }) => {
+            let value = if t.is_wide() {
+                Value::WString(U16String::from_str(&text))
+            } else {
+                Value::String(text)
+            };
+            if value.conforms_to(value_type) {
+                Ok(value)
+            } else {
+                Err(mismatch())
+            }
+        }
+        (
+            ValueType::BasicType(t),
+            Yaml::Scalar {
+                text,
+                quoted: false,
+            },
+        ) => basic_value(*t, &text, path).ok_or_else(mismatch)?,
+        _ => Err(mismatch()),
+    }
+}
+
+/// Parses a plain scalar as a value of `t`.
+///
+/// Returns `None` on a type mismatch, or `Some(Err(_))` if the value is out of range.
+fn basic_value(t: BasicType, text: &str, path: &str) -> Option<Result<Value, DynamicError>> {
+    let out_of_range = || DynamicError::OutOfRange {
+        path: path.into(),
+        value: text.into(),
+    };
+    macro_rules! int {
+        ($variant: ident) => {
+            Some(
+                parse_int(text)?
+                    .try_into()
+                    .map(Value::$variant)
+                    .map_err(|_| out_of_range()),
+            )
+        };
+    }
+
+    match t {
+        BasicType::Bool => match text {
+            "true" | "True" | "TRUE" | "yes" | "Yes" | "YES" | "on" | "On" | "ON" => {
+                Some(Ok(Value::Bool(true)))
+            }
+            "false" | "False" | "FALSE" | "no" | "No" | "NO" | "off" | "Off" | "OFF" => {
+                Some(Ok(Value::Bool(false)))
+            }
+            _ => None,
+        },
+        BasicType::I8 => int!(I8),
+        BasicType::I16 => int!(I16),
+        BasicType::I32 => int!(I32),
+        BasicType::I64 => int!(I64),
+        BasicType::U8 | BasicType::Char | BasicType::Byte => int!(U8),
+        BasicType::U16 => int!(U16),
+        BasicType::U32 => int!(U32),
+        BasicType::U64 => int!(U64),
+        BasicType::F32 => {
+            let v = parse_float(text)?;
+            Some(if v.is_finite() && v.abs() > f32::MAX.into() {
+                Err(out_of_range())
+            } else {
+                Ok(Value::F32(v as f32))
+            })
+        }
+        BasicType::F64 => Some(Ok(Value::F64(parse_float(text)?))),
+    }
+}
+
+fn parse_int(text: &str) -> Option<i128> {
+    let digits = text.replace('_', "");
+    let negative = digits.starts_with('-');
+    let digits = digits.strip_prefix(['-', '+']).unwrap_or(&digits);
+    let (digits, radix) = [("0x", 16), ("0o", 8), ("0b", 2)]
+        .iter()
+        .find_map(|&(prefix, radix)| digits.strip_prefix(prefix).map(|d| (d, radix)))
+        .unwrap_or((digits, 10));
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    // Too many digits are still an integer, just out of range.
+    let v = i128::from_str_radix(digits, radix).unwrap_or(i128::MAX);
+    Some(if negative { -v } else { v })
+}
+
+fn parse_float(text: &str) -> Option<f64> {
+    match text {
+        ".inf" | ".Inf" | ".INF" | "+.inf" | "+.Inf" | "+.INF" => Some(f64::INFINITY),
+        "-.inf" | "-.Inf" | "-.INF" => Some(f64::NEG_INFINITY),
+        ".nan" | ".NaN" | ".NAN" => Some(f64::NAN),
+        _ if text.contains(|c: char| c.is_ascii_alphabetic() && c != 'e' && c != 'E') => None,
+        _ => text.replace('_', "").parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn float_format() {
+        assert_eq!(format_float(0.), "0.0");
+        assert_eq!(format_float(-0.), "-0.0");
+        assert_eq!(format_float(1.5), "1.5");
+        assert_eq!(format_float(1e15), "1000000000000000.0");
+        assert_eq!(format_float(1e16), "1.0e+16");
+        assert_eq!(format_float(1.25e-5), "1.25e-05");
+        assert_eq!(format_float(0.1f32.into()), "0.10000000149011612");
+        assert_eq!(format_float(f64::NEG_INFINITY), "-.inf");
+        assert_eq!(format_float(f64::NAN), ".nan");
+    }
+
+    #[test]
+    fn string_quote() {
+        assert_eq!(quote("hello world", false), "hello world");
+        assert_eq!(quote("", false), "''");
+        assert_eq!(quote("it's", false), "it's");
+        assert_eq!(quote("'a'", false), "'''a'''");
+        assert_eq!(quote("123", false), "'123'");
+        assert_eq!(quote("1.5e3", false), "'1.5e3'");
+        assert_eq!(quote("true", false), "'true'");
+        assert_eq!(quote("a: b", false), "'a: b'");
+        assert_eq!(quote("- a", false), "'- a'");
+        assert_eq!(quote("-a", false), "-a");
+        assert_eq!(quote("a,b", false), "a,b");
+        assert_eq!(quote("a,b", true), "'a,b'");
+        assert_eq!(quote("...", false), "'...'");
+        assert_eq!(quote("a\nb", false), "\"a\\nb\"");
+    }
+
+    fn scalar(text: &str, quoted: bool) -> Yaml {
+        Yaml::Scalar {
+            text: text.into(),
+            quoted,
+        }
+    }
+
+    fn parse(s: &str) -> Result<Vec<(String, Yaml)>, DynamicError> {
+        Parser { s, pos: 0 }.parse_document()
+    }
+
+    #[test]
+    fn parse_flow() {
+        assert_eq!(
+            parse("{a: {x: 1.0}, b: [1, 'two', \"th\\\"ree\"], c: hello world, d: {}}").unwrap(),
+            vec![
+                (
+                    "a".into(),
+                    Yaml::Map(vec![("x".into(), scalar("1.0", false))])
+                ),
+                (
+                    "b".into(),
+                    Yaml::Seq(vec![
+                        scalar("1", false),
+                        scalar("two", true),
+                        scalar("th\"ree", true)
+                    ])
+                ),
+                ("c".into(), scalar("hello world", false)),
+                ("d".into(), Yaml::Map(vec![])),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_implicit_map() {
+        assert_eq!(
+            parse("data: 'it''s'\nvalue: -2  # comment\n").unwrap(),
+            vec![
+                ("data".into(), scalar("it's", true)),
+                ("value".into(), scalar("-2", false)),
+            ]
+        );
+        assert_eq!(parse("  ").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn parse_block() {
+        assert_eq!(
+            parse("a:\n  x: 1\n  y: [1, 2]\nb:\n- x: 2\n  y: a, b\n- 'c'\nc: {}\n").unwrap(),
+            vec![
+                (
+                    "a".into(),
+                    Yaml::Map(vec![
+                        ("x".into(), scalar("1", false)),
+                        (
+                            "y".into(),
+                            Yaml::Seq(vec![scalar("1", false), scalar("2", false)])
+                        ),
+                    ])
+                ),
+                (
+                    "b".into(),
+                    Yaml::Seq(vec![
+                        Yaml::Map(vec![
+                            ("x".into(), scalar("2", false)),
+                            ("y".into(), scalar("a, b", false)),
+                        ]),
+                        scalar("c", true),
+                    ])
+                ),
+                ("c".into(), Yaml::Map(vec![])),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(parse("{a: 1").is_err());
+        assert!(parse("{a: [1, 2}").is_err());
+        assert!(parse("{a: 'x}").is_err());
+        assert!(parse("{a: 1} b").is_err());
+        assert!(parse("a: 1\n  b: 2").is_err());
+    }
+
+    #[test]
+    fn parse_numbers() {
+        assert_eq!(parse_int("-0x10"), Some(-16));
+        assert_eq!(parse_int("1_000"), Some(1000));
+        assert_eq!(parse_int("1.0"), None);
+        assert_eq!(parse_float("1e3"), Some(1000.));
+        assert_eq!(parse_float("-.inf"), Some(f64::NEG_INFINITY));
+        assert_eq!(parse_float("abc"), None);
+    }
+}
@@ -0,0 +1,97 @@
+use anyhow::Result;
+use rclrust_msg_core::cdr;
+use rclrust_msg_core::error::DynamicError;
+use rclrust_msg_core::{DynamicMessage, TypeResolver, Value};
+use rclrust_msg_parser::msg::parse_message_string;
+
+fn resolver() -> Result<TypeResolver> {
+    let mut resolver = TypeResolver::new();
+    resolver.add_message(parse_message_string(
+        "geometry_msgs",
+        "Point",
+        "float64 x\nfloat64 y\nfloat64 z 1.5\n",
+    )?);
+    resolver.add_message(parse_message_string(
+        "geometry_msgs",
+        "Pose",
+        "Point position\nstring<=8 frame_id \"map\"\nuint8[3] flags [1, 2, 3]\n",
+    )?);
+    resolver.add_message(parse_message_string(
+        "geometry_msgs",
+        "PoseArray",
+        "geometry_msgs/Pose[<=2] poses\n",
+    )?);
+    Ok(resolver)
+}
+
+#[test]
+fn default_values() -> Result<()> {
+    let msg = DynamicMessage::new(resolver()?.resolve("geometry_msgs/Pose")?)?;
+
+    assert_eq!(msg.get("position.x")?, &Value::F64(0.));
+    assert_eq!(msg.get("position.z")?, &Value::F64(1.5));
+    assert_eq!(msg.get("frame_id")?, &Value::String("map".into()));
+    assert_eq!(msg.get("flags[2]")?, &Value::U8(3));
+    Ok(())
+}
+
+#[test]
+fn set_by_path() -> Result<()> {
+    let resolver = resolver()?;
+    let mut msg = DynamicMessage::new(resolver.resolve("geometry_msgs/PoseArray")?)?;
+    let mut pose = DynamicMessage::new(resolver.resolve("geometry_msgs/Pose")?)?;
+    pose.set("position.y", 2.0)?;
+    msg.set("poses", Value::Sequence(vec![pose.into()]))?;
+    msg.set("poses[0].flags[1]", 5u8)?;
+
+    assert_eq!(msg.get("poses[0].position.y")?, &Value::F64(2.0));
+    assert_eq!(msg.get("poses[0].flags[1]")?, &Value::U8(5));
+    Ok(())
+}
+
+#[test]
+fn set_errors() -> Result<()> {
+    let mut msg = DynamicMessage::new(resolver()?.resolve("geometry_msgs/Pose")?)?;
+
+    assert_eq!(
+        msg.set("position.w", 1.0),
+        Err(DynamicError::UnknownField("position.w".into()))
+    );
+    assert_eq!(
+        msg.set("position.x", 1),
+        Err(DynamicError::TypeMismatch {
+            path: "position.x".into(),
+            expected: "float64".into()
+        })
+    );
+    assert_eq!(
+        msg.set("frame_id", "too long name"),
+        Err(DynamicError::TypeMismatch {
+            path: "frame_id".into(),
+            expected: "string<=8".into()
+        })
+    );
+    assert_eq!(
+        msg.set("flags[3]", 0u8),
+        Err(DynamicError::IndexOutOfBounds {
+            path: "flags[3]".into(),
+            index: 3
+        })
+    );
+    Ok(())
+}
+
+#[test]
+fn cdr_round_trip() -> Result<()> {
+    let resolver = resolver()?;
+    let description = resolver.resolve("geometry_msgs/PoseArray")?;
+    let mut msg = DynamicMessage::new(description)?;
+    let pose = DynamicMessage::new(resolver.resolve("geometry_msgs/Pose")?)?;
+    msg.set(
+        "poses",
+        Value::Sequence(vec![pose.clone().into(), pose.into()]),
+    )?;
+
+    assert_eq!(cdr::decode(description, &cdr::encode(&msg))?, msg);
+    Ok(())
+}
@@ -0,0 +1,353 @@
+use anyhow::Result;
+use rclrust_msg_core::error::DynamicError;
+use rclrust_msg_core::yaml::{parse_yaml, to_yaml, YamlOptions};
+use rclrust_msg_core::{DynamicMessage, TypeResolver, Value};
+use rclrust_msg_parser::msg::parse_message_string;
+
+fn resolver() -> Result<TypeResolver> {
+    let mut resolver = TypeResolver::new();
+    resolver.add_message(parse_message_string(
+        "builtin_interfaces",
+        "Time",
+        "int32 sec\nuint32 nanosec\n",
+    )?);
+    resolver.add_message(parse_message_string(
+        "std_msgs",
+        "Header",
+        "builtin_interfaces/Time stamp\nstring frame_id\n",
+    )?);
+    resolver.add_message(parse_message_string("std_msgs", "Empty", "")?);
+    resolver.add_message(parse_message_string(
+        "geometry_msgs",
+        "Point",
+        "float64 x\nfloat64 y\nfloat64 z\n",
+    )?);
+    resolver.add_message(parse_message_string(
+        "geometry_msgs",
+        "Quaternion",
+        "float64 x 0\nfloat64 y 0\nfloat64 z 0\nfloat64 w 1\n",
+    )?);
+    resolver.add_message(parse_message_string(
+        "geometry_msgs",
+        "Vector3",
+        "float64 x\nfloat64 y\nfloat64 z\n",
+    )?);
+    resolver.add_message(parse_message_string(
+        "geometry_msgs",
+        "Twist",
+        "Vector3 linear\nVector3 angular\n",
+    )?);
+    resolver.add_message(parse_message_string(
+        "geometry_msgs",
+        "Pose",
+        "Point position\nQuaternion orientation\n",
+    )?);
+    resolver.add_message(parse_message_string(
+        "geometry_msgs",
+        "PoseArray",
+        "std_msgs/Header header\nPose[] poses\n",
+    )?);
+    resolver.add_message(parse_message_string(
+        "sensor_msgs",
+        "JointState",
+        "std_msgs/Header header\nstring[] name\nfloat64[] position\nfloat64[] velocity\nfloat64[] effort\n",
+    )?);
+    resolver.add_message(parse_message_string(
+        "test_msgs",
+        "Arrays",
+        "bool[3] bool_values\nfloat32[2] float32_values\nstring[2] string_values\nstd_msgs/Empty[1] empty_values\n",
+    )?);
+    resolver.add_message(parse_message_string(
+        "test_msgs",
+        "BoundedSequences",
+        "byte[<=4] byte_values\nstring<=8[<=2] string_values\n",
+    )?);
+    Ok(resolver)
+}
+
+#[test]
+fn echo_header() -> Result<()> {
+    let mut msg = DynamicMessage::new(resolver()?.resolve("std_msgs/Header")?)?;
+    msg.set("stamp.sec", 1_600_000_000)?;
+    msg.set("stamp.nanosec", 500u32)?;
+
+    assert_eq!(
+        to_yaml(&msg, &YamlOptions::default()),
+        "\
+stamp:
+  sec: 1600000000
+  nanosec: 500
+frame_id: ''
+"
+    );
+    Ok(())
+}
+
+#[test]
+fn echo_empty() -> Result<()> {
+    let msg = DynamicMessage::new(resolver()?.resolve("std_msgs/Empty")?)?;
+    assert_eq!(to_yaml(&msg, &YamlOptions::default()), "{}\n");
+    Ok(())
+}
+
+#[test]
+fn echo_sequence_of_messages() -> Result<()> {
+    let resolver = resolver()?;
+    let mut msg = DynamicMessage::new(resolver.resolve("geometry_msgs/PoseArray")?)?;
+    let mut pose = DynamicMessage::new(resolver.resolve("geometry_msgs/Pose")?)?;
+    pose.set("position.x", 1.5)?;
+    msg.set("header.frame_id", "map")?;
+    msg.set(
+        "poses",
+        Value::Sequence(vec![
+            pose.into(),
+            DynamicMessage::new(resolver.resolve("geometry_msgs/Pose")?)?.into(),
+        ]),
+    )?;
+
+    assert_eq!(
+        to_yaml(&msg, &YamlOptions::default()),
+        "\
+header:
+  stamp:
+    sec: 0
+    nanosec: 0
+  frame_id: map
+poses:
+- position:
+    x: 1.5
+    y: 0.0
+    z: 0.0
+  orientation:
+    x: 0.0
+    y: 0.0
+    z: 0.0
+    w: 1.0
+- position:
+    x: 0.0
+    y: 0.0
+    z: 0.0
+  orientation:
+    x: 0.0
+    y: 0.0
+    z: 0.0
+    w: 1.0
+"
+    );
+    Ok(())
+}
+
+fn joint_state() -> Result<DynamicMessage> {
+    let mut msg = DynamicMessage::new(resolver()?.resolve("sensor_msgs/JointState")?)?;
+    msg.set(
+        "name",
+        Value::Sequence(vec!["shoulder".into(), "elbow: 1".into(), "wrist".into()]),
+    )?;
+    msg.set(
+        "position",
+        Value::Sequence(vec![0.1.into(), (-2.5e-5).into(), 3.0.into()]),
+    )?;
+    Ok(msg)
+}
+
+#[test]
+fn echo_primitive_sequences() -> Result<()> {
+    assert_eq!(
+        to_yaml(&joint_state()?, &YamlOptions::default()),
+        "\
+header:
+  stamp:
+    sec: 0
+    nanosec: 0
+  frame_id: ''
+name: [shoulder, 'elbow: 1', wrist]
+position: [0.1, -2.5e-05, 3.0]
+velocity: []
+effort: []
+"
+    );
+    Ok(())
+}
+
+#[test]
+fn echo_truncate_length() -> Result<()> {
+    let options = YamlOptions {
+        truncate_length: Some(2),
+        ..Default::default()
+    };
+    assert_eq!(
+        to_yaml(&joint_state()?, &options),
+        "\
+header:
+  stamp:
+    sec: 0
+    nanosec: 0
+  frame_id: ''
+name: [sh..., el..., '...']
+position: [0.1, -2.5e-05, '...']
+velocity: []
+effort: []
+"
+    );
+    Ok(())
+}
+
+#[test]
+fn echo_no_arr() -> Result<()> {
+    let options = YamlOptions {
+        no_arr: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        to_yaml(&joint_state()?, &options),
+        "\
+header:
+  stamp:
+    sec: 0
+    nanosec: 0
+  frame_id: ''
+name: '<sequence type: string, length: 3>'
+position: '<sequence type: double, length: 3>'
+velocity: '<sequence type: double, length: 0>'
+effort: '<sequence type: double, length: 0>'
+"
+    );
+    Ok(())
+}
+
+#[test]
+fn echo_arrays() -> Result<()> {
+    let resolver = resolver()?;
+    let mut msg = DynamicMessage::new(resolver.resolve("test_msgs/Arrays")?)?;
+    msg.set("bool_values[1]", true)?;
+    msg.set("float32_values[0]", 0.5f32)?;
+    msg.set("string_values[0]", "a,b")?;
+
+    assert_eq!(
+        to_yaml(&msg, &YamlOptions::default()),
+        "\
+bool_values: [false, true, false]
+float32_values: [0.5, 0.0]
+string_values: ['a,b', '']
+empty_values:
+- {}
+"
+    );
+
+    let options = YamlOptions {
+        no_arr: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        to_yaml(&msg, &options),
+        "\
+bool_values: '<array type: boolean[3]>'
+float32_values: '<array type: float[2]>'
+string_values: '<array type: string[2]>'
+empty_values: '<array type: std_msgs/msg/Empty[1]>'
+"
+    );
+
+    let mut msg = DynamicMessage::new(resolver.resolve("test_msgs/BoundedSequences")?)?;
+    msg.set("string_values", Value::Sequence(vec!["a".into()]))?;
+    assert_eq!(
+        to_yaml(&msg, &options),
+        "\
+byte_values: '<sequence type: octet[4], length: 0>'
+string_values: '<sequence type: string[2], length: 1>'
+"
+    );
+    Ok(())
+}
+
+#[test]
+fn pub_twist() -> Result<()> {
+    let msg = parse_yaml(
+        resolver()?.resolve("geometry_msgs/Twist")?,
+        "{linear: {x: 1.0}, angular: {z: 0.5}}",
+    )?;
+    assert_eq!(msg.get("linear.x")?, &Value::F64(1.0));
+    assert_eq!(msg.get("linear.y")?, &Value::F64(0.0));
+    assert_eq!(msg.get("angular.z")?, &Value::F64(0.5));
+    Ok(())
+}
+
+#[test]
+fn pub_keeps_defaults() -> Result<()> {
+    let resolver = resolver()?;
+    let msg = parse_yaml(
+        resolver.resolve("geometry_msgs/PoseArray")?,
+        "header: {frame_id: map}\nposes: [{position: {x: 1}}, {}]",
+    )?;
+    assert_eq!(msg.get("header.frame_id")?, &Value::String("map".into()));
+    assert_eq!(msg.get("poses[0].position.x")?, &Value::F64(1.0));
+    assert_eq!(msg.get("poses[1].orientation.w")?, &Value::F64(1.0));
+    Ok(())
+}
+
+#[test]
+fn pub_round_trip() -> Result<()> {
+    let resolver = resolver()?;
+    let description = resolver.resolve("sensor_msgs/JointState")?;
+    let msg = parse_yaml(
+        description,
+        "{name: [a, 'b, c'], position: [1.5, -2.5e-05], effort: []}",
+    )?;
+    let yaml = to_yaml(&msg, &YamlOptions::default());
+    assert_eq!(parse_yaml(description, &yaml)?, msg);
+    Ok(())
+}
+
+#[test]
+fn pub_errors() -> Result<()> {
+    let resolver = resolver()?;
+    let twist = resolver.resolve("geometry_msgs/Twist")?;
+    assert_eq!(
+        parse_yaml(twist, "{linear: {w: 1.0}}"),
+        Err(DynamicError::UnknownField("linear.w".into()))
+    );
+    assert_eq!(
+        parse_yaml(twist, "{linear: {x: 'fast'}}"),
+        Err(DynamicError::TypeMismatch {
+            path: "linear.x".into(),
+            expected: "float64".into()
+        })
+    );
+    assert_eq!(
+        parse_yaml(twist, "{linear: 1.0}"),
+        Err(DynamicError::TypeMismatch {
+            path: "linear".into(),
+            expected: "geometry_msgs/Vector3".into()
+        })
+    );
+    assert!(matches!(
+        parse_yaml(twist, "{linear: {x: 1.0}"),
+        Err(DynamicError::YamlParseError(_))
+    ));
+
+    let header = resolver.resolve("std_msgs/Header")?;
+    assert_eq!(
+        parse_yaml(header, "stamp: {sec: 1, nanosec: -1}"),
+        Err(DynamicError::OutOfRange {
+            path: "stamp.nanosec".into(),
+            value: "-1".into()
+        })
+    );
+    assert_eq!(
+        parse_yaml(header, "stamp: {sec: 4294967296}"),
+        Err(DynamicError::OutOfRange {
+            path: "stamp.sec".into(),
+            value: "4294967296".into()
+        })
+    );
+
+    let arrays = resolver.resolve("test_msgs/Arrays")?;
+    assert_eq!(
+        parse_yaml(arrays, "bool_values: [true]"),
+        Err(DynamicError::TypeMismatch {
+            path: "bool_values".into(),
+            expected: "bool[3]".into()
+        })
+    );
+    Ok(())
+}
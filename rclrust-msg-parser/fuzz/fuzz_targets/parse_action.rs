@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rclrust_msg_parser::action::parse_action_string;
+
+fuzz_target!(|data: &str| {
+    let _ = parse_action_string("pkg", "Action", data);
+});
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rclrust_msg_parser::msg::parse_message_string;
+
+fuzz_target!(|data: &str| {
+    let _ = parse_message_string("pkg", "Msg", data);
+});
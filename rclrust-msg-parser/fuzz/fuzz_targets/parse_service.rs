@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rclrust_msg_parser::srv::parse_service_string;
+
+fuzz_target!(|data: &str| {
+    let _ = parse_service_string("pkg", "Srv", data);
+});
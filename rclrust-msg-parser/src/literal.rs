@@ -106,9 +106,9 @@ fn bool_literal(s: &str) -> IResult<&str, bool> {
     ))(s)
 }
 
-pub fn get_string_literal_parser(
-    string_type: GenericString,
-) -> Box<dyn FnMut(&str) -> IResult<&str, String>> {
+type StringLiteralParser = Box<dyn FnMut(&str) -> IResult<&str, String>>;
+
+pub fn get_string_literal_parser(string_type: GenericString) -> StringLiteralParser {
     match string_type {
         GenericString::String | GenericString::WString => Box::new(string_literal),
         GenericString::BoundedString(max_size) | GenericString::BoundedWString(max_size) => {
@@ -245,6 +245,7 @@ mod test {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn parse_bool_literal() -> Result<()> {
         assert_eq!(bool_literal("true")?.1, true);
         assert_eq!(bool_literal("false")?.1, false);
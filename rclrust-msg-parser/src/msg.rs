@@ -43,7 +43,8 @@ pub fn parse_message_string(
 
         let (_, rest) = split_once(line, ' ');
 
-        match rest.unwrap().find('=') {
+        // Lines without a name are left to `member_def` to report
+        match rest.and_then(|rest| rest.find('=')) {
             Some(_) => constants.push(constant_def(line)?),
             None => members.push(member_def(line)?),
         }
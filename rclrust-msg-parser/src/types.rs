@@ -100,20 +100,12 @@ fn generic_string(s: &str) -> IResult<&str, GenericString> {
             alt((tag("string"), tag("wstring"))),
             opt(preceded(tag("<="), usize_literal)),
         ),
-        |(type_str, array_info)| {
-            if let Some(max_size) = array_info {
-                match type_str {
-                    "string" => GenericString::BoundedString(max_size),
-                    "wstring" => GenericString::BoundedWString(max_size),
-                    _ => unreachable!(),
-                }
-            } else {
-                match type_str {
-                    "string" => GenericString::String,
-                    "wstring" => GenericString::WString,
-                    _ => unreachable!(),
-                }
-            }
+        |(type_str, array_info)| match (type_str, array_info) {
+            ("string", Some(max_size)) => GenericString::BoundedString(max_size),
+            ("wstring", Some(max_size)) => GenericString::BoundedWString(max_size),
+            ("string", None) => GenericString::String,
+            ("wstring", None) => GenericString::WString,
+            _ => unreachable!(),
         },
     )(s)
 }
@@ -0,0 +1,95 @@
+use proptest::prelude::*;
+use rclrust_msg_parser::action::parse_action_string;
+use rclrust_msg_parser::msg::parse_message_string;
+use rclrust_msg_parser::srv::parse_service_string;
+
+/// Fragments of interface definitions to reach deeper into the parsers than random strings
+const TOKENS: &[&str] = &[
+    " ",
+    "\n",
+    "\t",
+    "#",
+    "---",
+    "\n---\n",
+    "=",
+    "<=",
+    "[",
+    "]",
+    "[]",
+    "[<=",
+    ",",
+    "\"",
+    "'",
+    "\\",
+    "/",
+    "-",
+    "+",
+    "_",
+    ".",
+    "0",
+    "3",
+    "255",
+    "-129",
+    "1e400",
+    "0x",
+    "0b",
+    "0o",
+    "true",
+    "false",
+    "bool",
+    "byte",
+    "char",
+    "int8",
+    "uint8",
+    "int32",
+    "uint64",
+    "float32",
+    "float64",
+    "string",
+    "wstring",
+    "Header",
+    "std_msgs/Header",
+    "data",
+    "DATA",
+    "name_",
+    "__x",
+];
+
+fn interface_string() -> impl Strategy<Value = String> {
+    prop::collection::vec(
+        prop_oneof![
+            3 => prop::sample::select(TOKENS).prop_map(String::from),
+            1 => any::<String>(),
+        ],
+        0..32,
+    )
+    .prop_map(|fragments| fragments.concat())
+}
+
+proptest! {
+    #[test]
+    fn parse_message_never_panics(s in interface_string()) {
+        let _ = parse_message_string("pkg", "Msg", &s);
+    }
+
+    #[test]
+    fn parse_service_never_panics(s in interface_string()) {
+        let _ = parse_service_string("pkg", "Srv", &s);
+    }
+
+    #[test]
+    fn parse_action_never_panics(s in interface_string()) {
+        let _ = parse_action_string("pkg", "Action", &s);
+    }
+}
+
+/// Inputs which made the parsers panic
+const REGRESSIONS: &[&str] = &["int32", "int32 # no name", "bool\n---\n"];
+
+#[test]
+fn parse_regressions() {
+    for s in REGRESSIONS {
+        assert!(parse_message_string("pkg", "Msg", s).is_err());
+        assert!(parse_service_string("pkg", "Srv", s).is_err());
+    }
+}
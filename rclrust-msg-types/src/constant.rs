@@ -1,3 +1,5 @@
+use std::fmt;
+
 use crate::define_enum_from;
 use crate::primitives::{BasicType, GenericUnboundedString, PrimitiveType};
 use crate::sequences::PrimitiveArray;
@@ -26,3 +28,13 @@ impl From<PrimitiveType> for ConstantType {
         }
     }
 }
+
+impl fmt::Display for ConstantType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BasicType(t) => write!(f, "{}", t),
+            Self::GenericUnboundedString(t) => write!(f, "{}", t),
+            Self::PrimitiveArray(t) => write!(f, "{}[{}]", t.value_type, t.size),
+        }
+    }
+}
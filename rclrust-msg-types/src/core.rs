@@ -1,8 +1,8 @@
 use crate::primitives::*;
-use crate::{ConstantType, MemberType};
+use crate::{BoundedSequence, ConstantType, Distro, MemberType};
 
 /// A member of a structure
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Member {
     /// The name of the member
     pub name: String,
@@ -36,6 +36,54 @@ pub struct Message {
     pub constants: Vec<Constant>,
 }
 
+/// How the C structure of a message is bounded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MessageClass {
+    /// Only basic types, arrays of them and other plain messages, i.e. a fixed-size structure
+    /// without pointers
+    Plain,
+    /// Only bounded strings and sequences, i.e. a bounded serialized size
+    Bounded,
+    /// Some unbounded strings or sequences
+    Unbounded,
+}
+
+impl Message {
+    /// Classifies the message, looking up the nested types with `resolve`.
+    ///
+    /// Nested types which `resolve` does not find are regarded as unbounded.
+    pub fn class(&self, resolve: &dyn Fn(&NamespacedType) -> Option<Self>) -> MessageClass {
+        let nested_class = |t: &NamespacedType| {
+            resolve(t).map_or(MessageClass::Unbounded, |msg| msg.class(resolve))
+        };
+        let nestable_class = |t: NestableType| match t {
+            NestableType::BasicType(_) => MessageClass::Plain,
+            NestableType::NamedType(t) => nested_class(&NamespacedType {
+                package: self.package.clone(),
+                namespace: "msg".into(),
+                name: t.0,
+            }),
+            NestableType::NamespacedType(t) => nested_class(&t),
+            NestableType::GenericString(GenericString::String | GenericString::WString) => {
+                MessageClass::Unbounded
+            }
+            NestableType::GenericString(_) => MessageClass::Bounded,
+        };
+
+        self.members
+            .iter()
+            .map(|member| match member.r#type.clone() {
+                MemberType::Sequence(_) => MessageClass::Unbounded,
+                MemberType::BoundedSequence(t) => {
+                    MessageClass::Bounded.max(nestable_class(t.value_type))
+                }
+                t => nestable_class(t.inner_type()),
+            })
+            .max()
+            .unwrap_or(MessageClass::Plain)
+    }
+}
+
 /// A service definition
 #[derive(Debug, Clone)]
 pub struct Service {
@@ -49,6 +97,50 @@ pub struct Service {
     pub response: Message,
 }
 
+impl Service {
+    /// Returns the message of the events of the service in `namespace`, i.e. `srv` or `action`, if
+    /// `distro` has them.
+    pub fn event_msg(&self, namespace: &str, distro: Distro) -> Option<Message> {
+        if !distro.has_service_events() {
+            return None;
+        }
+
+        let sample = |name: &str, msg: &Message| Member {
+            name: name.into(),
+            r#type: BoundedSequence {
+                value_type: NamespacedType {
+                    package: self.package.clone(),
+                    namespace: namespace.into(),
+                    name: msg.name.clone(),
+                }
+                .into(),
+                max_size: 1,
+            }
+            .into(),
+            default: None,
+        };
+        Some(Message {
+            package: self.package.clone(),
+            name: format!("{}_Event", self.name),
+            members: vec![
+                Member {
+                    name: "info".into(),
+                    r#type: NamespacedType {
+                        package: "service_msgs".into(),
+                        namespace: "msg".into(),
+                        name: "ServiceEventInfo".into(),
+                    }
+                    .into(),
+                    default: None,
+                },
+                sample("request", &self.request),
+                sample("response", &self.response),
+            ],
+            constants: vec![],
+        })
+    }
+}
+
 /// An action definition
 #[derive(Debug, Clone)]
 pub struct Action {
@@ -65,7 +157,11 @@ pub struct Action {
 }
 
 impl Action {
-    pub fn send_goal_srv(&self) -> Service {
+    /// Returns the `<Action>_SendGoal` service of `distro`.
+    ///
+    /// Its request and response are the same in all supported distros, while its events are given
+    /// by [`Service::event_msg`] with the same `distro`.
+    pub fn send_goal_srv(&self, _distro: Distro) -> Service {
         let common = format!("{}_SendGoal", self.name);
 
         let request = Message {
@@ -117,7 +213,8 @@ impl Action {
         }
     }
 
-    pub fn get_result_srv(&self) -> Service {
+    /// Returns the `<Action>_GetResult` service of `distro`, see [`Action::send_goal_srv`].
+    pub fn get_result_srv(&self, _distro: Distro) -> Service {
         let common = format!("{}_GetResult", self.name);
 
         let request = Message {
@@ -157,7 +254,9 @@ impl Action {
         }
     }
 
-    pub fn feedback_message_msg(&self) -> Message {
+    /// Returns the `<Action>_FeedbackMessage` message of `distro`, which is the same in all
+    /// supported distros.
+    pub fn feedback_message_msg(&self, _distro: Distro) -> Message {
         Message {
             package: self.package.clone(),
             name: format!("{}_FeedbackMessage", self.name),
@@ -0,0 +1,570 @@
+//! Changes between two versions of interfaces and their wire compatibility
+//!
+//! A change is wire-breaking when a message serialized with one version cannot be deserialized
+//! correctly with the other one.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::core::{Member, Message};
+use crate::member::MemberType;
+use crate::primitives::{BasicType, GenericString, NestableType};
+use crate::sequences::{BoundedSequence, Sequence};
+
+/// A change of a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    MessageAdded,
+    MessageRemoved,
+    FieldAdded {
+        field: String,
+        r#type: MemberType,
+    },
+    FieldRemoved {
+        field: String,
+        r#type: MemberType,
+    },
+    /// A field renamed without changing its position and type
+    FieldRenamed {
+        from: String,
+        to: String,
+    },
+    /// The order of the fields kept in both versions changed.
+    FieldsReordered {
+        from: Vec<String>,
+        to: Vec<String>,
+    },
+    TypeChanged {
+        field: String,
+        from: MemberType,
+        to: MemberType,
+    },
+    /// Only the bounds of a string or a sequence changed.
+    BoundChanged {
+        field: String,
+        from: MemberType,
+        to: MemberType,
+    },
+    DefaultChanged {
+        field: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    /// A nested message type of the field has wire-breaking changes.
+    NestedTypeChanged {
+        field: String,
+        r#type: MemberType,
+    },
+    ConstantAdded {
+        name: String,
+    },
+    ConstantRemoved {
+        name: String,
+    },
+    ConstantChanged {
+        name: String,
+        from: String,
+        to: String,
+    },
+}
+
+impl Change {
+    pub fn is_wire_breaking(&self) -> bool {
+        match self {
+            Self::MessageRemoved
+            | Self::FieldAdded { .. }
+            | Self::FieldRemoved { .. }
+            | Self::FieldsReordered { .. }
+            | Self::NestedTypeChanged { .. }
+            // Bounds are not serialized, but each side rejects messages beyond its own bounds,
+            // whichever of them is tighter.
+            | Self::BoundChanged { .. } => true,
+            Self::TypeChanged { from, to, .. } => normalize_aliases(from) != normalize_aliases(to),
+            Self::MessageAdded
+            | Self::FieldRenamed { .. }
+            | Self::DefaultChanged { .. }
+            | Self::ConstantAdded { .. }
+            | Self::ConstantRemoved { .. }
+            | Self::ConstantChanged { .. } => false,
+        }
+    }
+
+    /// Returns the name of the field in the new version which the change is about.
+    fn field(&self) -> Option<&str> {
+        match self {
+            Self::FieldAdded { field, .. }
+            | Self::FieldRemoved { field, .. }
+            | Self::TypeChanged { field, .. }
+            | Self::BoundChanged { field, .. }
+            | Self::DefaultChanged { field, .. }
+            | Self::NestedTypeChanged { field, .. } => Some(field),
+            Self::FieldRenamed { to, .. } => Some(to),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let or_none = |v: &Option<String>| v.clone().unwrap_or_else(|| "none".into());
+        match self {
+            Self::MessageAdded => write!(f, "message added"),
+            Self::MessageRemoved => write!(f, "message removed"),
+            Self::FieldAdded { field, r#type } => write!(f, "field `{} {}` added", r#type, field),
+            Self::FieldRemoved { field, r#type } => {
+                write!(f, "field `{} {}` removed", r#type, field)
+            }
+            Self::FieldRenamed { from, to } => {
+                write!(f, "field `{}` renamed to `{}`", from, to)
+            }
+            Self::FieldsReordered { from, to } => write!(
+                f,
+                "fields reordered from ({}) to ({})",
+                from.join(", "),
+                to.join(", ")
+            ),
+            Self::TypeChanged { field, from, to } => {
+                write!(f, "type of `{}` changed from {} to {}", field, from, to)
+            }
+            Self::BoundChanged { field, from, to } => {
+                write!(f, "bound of `{}` changed from {} to {}", field, from, to)
+            }
+            Self::DefaultChanged { field, from, to } => write!(
+                f,
+                "default of `{}` changed from {} to {}",
+                field,
+                or_none(from),
+                or_none(to)
+            ),
+            Self::NestedTypeChanged { field, r#type } => {
+                write!(f, "nested type {} of `{}` changed", r#type, field)
+            }
+            Self::ConstantAdded { name } => write!(f, "constant `{}` added", name),
+            Self::ConstantRemoved { name } => write!(f, "constant `{}` removed", name),
+            Self::ConstantChanged { name, from, to } => {
+                write!(f, "constant `{}` changed from `{}` to `{}`", name, from, to)
+            }
+        }
+    }
+}
+
+/// Changes of a message in a package
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageDiff {
+    pub package: String,
+    pub name: String,
+    pub changes: Vec<Change>,
+}
+
+impl MessageDiff {
+    pub fn is_wire_breaking(&self) -> bool {
+        self.changes.iter().any(Change::is_wire_breaking)
+    }
+}
+
+/// Returns the changes from `old` to `new`, ignoring their package and name.
+pub fn diff_message(old: &Message, new: &Message) -> Vec<Change> {
+    let mut changes = Vec::new();
+
+    let is_removed = |m: &Member| !new.members.iter().any(|n| n.name == m.name);
+    let is_added = |m: &Member| !old.members.iter().any(|o| o.name == m.name);
+    let renamed = old
+        .members
+        .iter()
+        .zip(&new.members)
+        .filter(|(o, n)| o.r#type == n.r#type && is_removed(o) && is_added(n))
+        .collect::<Vec<_>>();
+    for m in old.members.iter().filter(|&m| is_removed(m)) {
+        changes.push(renamed.iter().find(|(o, _)| o.name == m.name).map_or_else(
+            || Change::FieldRemoved {
+                field: m.name.clone(),
+                r#type: m.r#type.clone(),
+            },
+            |(_, n)| Change::FieldRenamed {
+                from: m.name.clone(),
+                to: n.name.clone(),
+            },
+        ));
+    }
+    changes.extend(
+        new.members
+            .iter()
+            .filter(|&m| is_added(m) && !renamed.iter().any(|(_, n)| n.name == m.name))
+            .map(|m| Change::FieldAdded {
+                field: m.name.clone(),
+                r#type: m.r#type.clone(),
+            }),
+    );
+
+    let kept = |from: &Message, other: &Message| {
+        from.members
+            .iter()
+            .filter(|m| other.members.iter().any(|o| o.name == m.name))
+            .map(|m| m.name.clone())
+            .collect::<Vec<_>>()
+    };
+    let (kept_old, kept_new) = (kept(old, new), kept(new, old));
+    if kept_old != kept_new {
+        changes.push(Change::FieldsReordered {
+            from: kept_old,
+            to: kept_new,
+        });
+    }
+
+    for new_member in &new.members {
+        let old_member = match old.members.iter().find(|m| m.name == new_member.name) {
+            Some(m) => m,
+            None => continue,
+        };
+        let field = new_member.name.clone();
+        if old_member.r#type != new_member.r#type {
+            let (from, to) = (old_member.r#type.clone(), new_member.r#type.clone());
+            changes.push(if strip_bounds(&from) == strip_bounds(&to) {
+                Change::BoundChanged { field, from, to }
+            } else {
+                Change::TypeChanged { field, from, to }
+            });
+        } else if old_member.default != new_member.default {
+            changes.push(Change::DefaultChanged {
+                field,
+                from: old_member.default.clone(),
+                to: new_member.default.clone(),
+            });
+        }
+    }
+
+    for constant in &old.constants {
+        match new.constants.iter().find(|c| c.name == constant.name) {
+            None => changes.push(Change::ConstantRemoved {
+                name: constant.name.clone(),
+            }),
+            Some(c) if c.r#type != constant.r#type || c.value != constant.value => {
+                changes.push(Change::ConstantChanged {
+                    name: constant.name.clone(),
+                    from: format!("{} {}", constant.r#type, constant.value),
+                    to: format!("{} {}", c.r#type, c.value),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    changes.extend(
+        new.constants
+            .iter()
+            .filter(|c| !old.constants.iter().any(|o| o.name == c.name))
+            .map(|c| Change::ConstantAdded {
+                name: c.name.clone(),
+            }),
+    );
+
+    changes
+}
+
+/// Returns the changes of the messages from `old` to `new`, matched by their packages and names.
+///
+/// Messages containing a message with wire-breaking changes are also reported as changed.
+pub fn diff_messages(old: &[Message], new: &[Message]) -> Vec<MessageDiff> {
+    let find = |msgs: &'_ [Message], package: &str, name: &str| {
+        msgs.iter()
+            .find(|m| m.package == package && m.name == name)
+            .cloned()
+    };
+
+    let mut diffs = Vec::new();
+    for old_msg in old {
+        let changes = find(new, &old_msg.package, &old_msg.name).map_or_else(
+            || vec![Change::MessageRemoved],
+            |new_msg| diff_message(old_msg, &new_msg),
+        );
+        diffs.push(MessageDiff {
+            package: old_msg.package.clone(),
+            name: old_msg.name.clone(),
+            changes,
+        });
+    }
+    for new_msg in new
+        .iter()
+        .filter(|m| find(old, &m.package, &m.name).is_none())
+    {
+        diffs.push(MessageDiff {
+            package: new_msg.package.clone(),
+            name: new_msg.name.clone(),
+            changes: vec![Change::MessageAdded],
+        });
+    }
+
+    // Propagate wire-breaking changes to the messages containing them
+    loop {
+        let breaking = diffs
+            .iter()
+            .filter(|d| d.is_wire_breaking())
+            .map(|d| (d.package.clone(), d.name.clone()))
+            .collect::<BTreeSet<_>>();
+        let mut propagated = false;
+        for (diff, msg) in diffs
+            .iter_mut()
+            .filter_map(|d| find(new, &d.package, &d.name).map(|m| (d, m)))
+        {
+            for member in &msg.members {
+                let nested = match member.r#type.clone().inner_type() {
+                    NestableType::NamedType(t) => (msg.package.clone(), t.0),
+                    NestableType::NamespacedType(t) => (t.package, t.name),
+                    _ => continue,
+                };
+                let reported = diff.changes.iter().any(|c| c.field() == Some(&member.name));
+                if breaking.contains(&nested) && !reported {
+                    diff.changes.push(Change::NestedTypeChanged {
+                        field: member.name.clone(),
+                        r#type: member.r#type.clone(),
+                    });
+                    propagated = true;
+                }
+            }
+        }
+        if !propagated {
+            break;
+        }
+    }
+
+    diffs.retain(|d| !d.changes.is_empty());
+    diffs.sort_by(|a, b| (&a.package, &a.name).cmp(&(&b.package, &b.name)));
+    diffs
+}
+
+/// `char` and `byte` are serialized in the same way as `uint8`.
+fn normalize_aliases(t: &MemberType) -> MemberType {
+    let normalize = |t: NestableType| match t {
+        NestableType::BasicType(BasicType::Char | BasicType::Byte) => BasicType::U8.into(),
+        t => t,
+    };
+    match t.clone() {
+        MemberType::Array(mut t) => {
+            t.value_type = normalize(t.value_type);
+            t.into()
+        }
+        MemberType::Sequence(mut t) => {
+            t.value_type = normalize(t.value_type);
+            t.into()
+        }
+        MemberType::BoundedSequence(mut t) => {
+            t.value_type = normalize(t.value_type);
+            t.into()
+        }
+        t => normalize(t.inner_type()).into(),
+    }
+}
+
+fn strip_string_bound(t: NestableType) -> NestableType {
+    match t {
+        NestableType::GenericString(GenericString::BoundedString(_)) => {
+            GenericString::String.into()
+        }
+        NestableType::GenericString(GenericString::BoundedWString(_)) => {
+            GenericString::WString.into()
+        }
+        t => t,
+    }
+}
+
+fn strip_bounds(t: &MemberType) -> MemberType {
+    match t.clone() {
+        MemberType::Array(mut t) => {
+            t.value_type = strip_string_bound(t.value_type);
+            t.into()
+        }
+        MemberType::Sequence(Sequence { value_type })
+        | MemberType::BoundedSequence(BoundedSequence { value_type, .. }) => Sequence {
+            value_type: strip_string_bound(value_type),
+        }
+        .into(),
+        t => strip_string_bound(t.inner_type()).into(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::Constant;
+    use crate::primitives::NamespacedType;
+
+    fn member(name: &str, r#type: impl Into<MemberType>) -> Member {
+        Member {
+            name: name.into(),
+            r#type: r#type.into(),
+            default: None,
+        }
+    }
+
+    fn message(name: &str, members: Vec<Member>) -> Message {
+        Message {
+            package: "test_msgs".into(),
+            name: name.into(),
+            members,
+            constants: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn fields() {
+        let old = message(
+            "Test",
+            vec![
+                member("a", BasicType::I32),
+                member("b", BasicType::F64),
+                member("c", BasicType::Bool),
+                member("d", BasicType::U8),
+            ],
+        );
+        let new = message(
+            "Test",
+            vec![
+                member("c", BasicType::Bool),
+                member("b2", BasicType::F64),
+                member("a", BasicType::I32),
+                member("e", BasicType::I16),
+            ],
+        );
+        let changes = diff_message(&old, &new);
+        assert_eq!(
+            changes,
+            [
+                Change::FieldRenamed {
+                    from: "b".into(),
+                    to: "b2".into()
+                },
+                Change::FieldRemoved {
+                    field: "d".into(),
+                    r#type: BasicType::U8.into()
+                },
+                Change::FieldAdded {
+                    field: "e".into(),
+                    r#type: BasicType::I16.into()
+                },
+                Change::FieldsReordered {
+                    from: vec!["a".into(), "c".into()],
+                    to: vec!["c".into(), "a".into()]
+                },
+            ]
+        );
+        assert_eq!(
+            changes
+                .iter()
+                .map(Change::is_wire_breaking)
+                .collect::<Vec<_>>(),
+            [false, true, true, true]
+        );
+        assert_eq!(changes[0].to_string(), "field `b` renamed to `b2`");
+    }
+
+    #[test]
+    fn types() {
+        let change = |from: MemberType, to: MemberType| {
+            let changes = diff_message(
+                &message("Test", vec![member("a", from)]),
+                &message("Test", vec![member("a", to)]),
+            );
+            assert_eq!(changes.len(), 1);
+            changes[0].clone()
+        };
+
+        let width = change(BasicType::I32.into(), BasicType::I64.into());
+        assert!(matches!(width, Change::TypeChanged { .. }));
+        assert!(width.is_wire_breaking());
+        assert_eq!(width.to_string(), "type of `a` changed from int32 to int64");
+        assert!(!change(BasicType::Byte.into(), BasicType::U8.into()).is_wire_breaking());
+
+        let loosened = change(
+            GenericString::BoundedString(8).into(),
+            GenericString::String.into(),
+        );
+        assert!(matches!(loosened, Change::BoundChanged { .. }));
+        assert!(loosened.is_wire_breaking());
+
+        let tightened = change(
+            Sequence {
+                value_type: BasicType::F64.into(),
+            }
+            .into(),
+            BoundedSequence {
+                value_type: BasicType::F64.into(),
+                max_size: 3,
+            }
+            .into(),
+        );
+        assert!(matches!(tightened, Change::BoundChanged { .. }));
+        assert!(tightened.is_wire_breaking());
+        assert_eq!(
+            tightened.to_string(),
+            "bound of `a` changed from float64[] to float64[<=3]"
+        );
+    }
+
+    #[test]
+    fn defaults_and_constants() {
+        let mut old = message("Test", vec![member("a", BasicType::I32)]);
+        old.constants.push(Constant {
+            name: "X".into(),
+            r#type: BasicType::I32.into(),
+            value: "1".into(),
+        });
+        let mut new = old.clone();
+        new.members[0].default = Some("3".into());
+        new.constants[0].value = "2".into();
+
+        let changes = diff_message(&old, &new);
+        assert_eq!(
+            changes,
+            [
+                Change::DefaultChanged {
+                    field: "a".into(),
+                    from: None,
+                    to: Some("3".into())
+                },
+                Change::ConstantChanged {
+                    name: "X".into(),
+                    from: "int32 1".into(),
+                    to: "int32 2".into()
+                },
+            ]
+        );
+        assert!(!changes.iter().any(Change::is_wire_breaking));
+    }
+
+    #[test]
+    fn nested() {
+        let stamped = |header: MemberType| message("Stamped", vec![member("header", header)]);
+        let header = NamespacedType {
+            package: "test_msgs".into(),
+            namespace: "msg".into(),
+            name: "Header".into(),
+        };
+        let old = vec![
+            message("Header", vec![member("stamp", BasicType::I32)]),
+            stamped(header.clone().into()),
+            message("Old", Vec::new()),
+        ];
+        let new = vec![
+            message("Header", vec![member("stamp", BasicType::I64)]),
+            stamped(header.into()),
+            message("New", Vec::new()),
+        ];
+
+        let diffs = diff_messages(&old, &new);
+        assert_eq!(
+            diffs
+                .iter()
+                .map(|d| (d.name.as_str(), d.is_wire_breaking()))
+                .collect::<Vec<_>>(),
+            [
+                ("Header", true),
+                ("New", false),
+                ("Old", true),
+                ("Stamped", true)
+            ]
+        );
+        assert_eq!(
+            diffs[3].changes[0].to_string(),
+            "nested type test_msgs/Header of `header` changed"
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use std::fmt;
+
+/// A ROS 2 distribution, which determines the interfaces synthesized for services and actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Distro {
+    Foxy,
+    Galactic,
+    Humble,
+    Iron,
+    Jazzy,
+    Kilted,
+    Rolling,
+}
+
+impl Distro {
+    pub fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "foxy" => Self::Foxy,
+            "galactic" => Self::Galactic,
+            "humble" => Self::Humble,
+            "iron" => Self::Iron,
+            "jazzy" => Self::Jazzy,
+            "kilted" => Self::Kilted,
+            "rolling" => Self::Rolling,
+            _ => return None,
+        })
+    }
+
+    /// Returns the distro of the sourced ROS environment, i.e. `$ROS_DISTRO`.
+    pub fn from_env() -> Option<Self> {
+        Self::parse(&std::env::var("ROS_DISTRO").ok()?)
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Foxy => "foxy",
+            Self::Galactic => "galactic",
+            Self::Humble => "humble",
+            Self::Iron => "iron",
+            Self::Jazzy => "jazzy",
+            Self::Kilted => "kilted",
+            Self::Rolling => "rolling",
+        }
+    }
+
+    /// Whether `rosidl_typesupport_introspection_c__MessageMember` has `fetch_function` and
+    /// `assign_function`, which Humble introduced.
+    pub const fn has_member_accessors(self) -> bool {
+        !matches!(self, Self::Foxy | Self::Galactic)
+    }
+
+    /// Whether services publish `<Service>_Event` messages, which Iron introduced.
+    pub const fn has_service_events(self) -> bool {
+        !matches!(self, Self::Foxy | Self::Galactic | Self::Humble)
+    }
+}
+
+impl fmt::Display for Distro {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
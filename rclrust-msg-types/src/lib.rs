@@ -7,6 +7,9 @@
 
 mod constant;
 mod core;
+pub mod diff;
+mod distro;
+pub mod literal;
 mod macros;
 mod member;
 mod primitives;
@@ -14,6 +17,7 @@ mod sequences;
 
 pub use crate::core::*;
 pub use constant::*;
+pub use distro::*;
 pub use member::*;
 pub use primitives::*;
 pub use sequences::*;
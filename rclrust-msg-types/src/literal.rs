@@ -0,0 +1,76 @@
+//! The syntax of values parsed by `rclrust-msg-parser`, i.e. [`Member::default`] and
+//! [`Constant::value`]
+//!
+//! Strings are written as raw string literals, e.g. `r#"a"#`, and arrays as comma-separated
+//! lists in brackets, e.g. `[1, 2]`.
+//!
+//! [`Member::default`]: crate::Member::default
+//! [`Constant::value`]: crate::Constant::value
+
+const STRING_START: &str = "r#\"";
+const STRING_END: &str = "\"#";
+
+/// Splits `s` at its first string literal into the text before it, the contents of the literal
+/// and the text after it.
+pub fn split_string(s: &str) -> Option<(&str, &str, &str)> {
+    let start = s.find(STRING_START)?;
+    let rest = &s[start + STRING_START.len()..];
+    let end = rest.find(STRING_END)?;
+    Some((&s[..start], &rest[..end], &rest[end + STRING_END.len()..]))
+}
+
+/// Returns the contents of a string literal, or `None` if `s` is not one.
+pub fn parse_string(s: &str) -> Option<&str> {
+    match split_string(s)? {
+        ("", value, "") => Some(value),
+        _ => None,
+    }
+}
+
+/// Splits an array literal into the literals of its elements, or returns `None` if `s` is not
+/// one.
+pub fn parse_sequence(s: &str) -> Option<Vec<&str>> {
+    let mut s = s.strip_prefix('[')?.strip_suffix(']')?.trim();
+    let mut values = Vec::new();
+    while !s.is_empty() {
+        let end = if s.starts_with(STRING_START) {
+            // String literals may contain `,`
+            s.len() - split_string(s)?.2.len()
+        } else {
+            s.find(',').unwrap_or(s.len())
+        };
+        values.push(s[..end].trim());
+        s = s[end..].trim_start();
+        s = s.strip_prefix(',').unwrap_or(s).trim_start();
+    }
+    Some(values)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn string() {
+        assert_eq!(parse_string(r##"r#"a, "b""#"##), Some(r#"a, "b""#));
+        assert_eq!(parse_string(r##"r#""#"##), Some(""));
+        assert_eq!(parse_string("1"), None);
+        assert_eq!(parse_string(r##"[r#"a"#]"##), None);
+        assert_eq!(
+            split_string(r##"from r#"a"# to r#"b"#"##),
+            Some(("from ", "a", r##" to r#"b"#"##))
+        );
+    }
+
+    #[test]
+    fn sequence() {
+        assert_eq!(parse_sequence("[1, -2,3]"), Some(vec!["1", "-2", "3"]));
+        assert_eq!(parse_sequence("[]"), Some(vec![]));
+        assert_eq!(
+            parse_sequence(r##"[r#"a, b"#, r#""#]"##),
+            Some(vec![r##"r#"a, b"#"##, r##"r#""#"##])
+        );
+        assert_eq!(parse_sequence("1"), None);
+        assert_eq!(parse_sequence(r##"[r#"a]"##), None);
+    }
+}
@@ -1,3 +1,5 @@
+use std::fmt;
+
 use crate::define_enum_from;
 use crate::primitives::*;
 use crate::sequences::*;
@@ -46,3 +48,14 @@ impl From<NestableType> for MemberType {
         }
     }
 }
+
+impl fmt::Display for MemberType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Array(t) => write!(f, "{}[{}]", t.value_type, t.size),
+            Self::Sequence(t) => write!(f, "{}[]", t.value_type),
+            Self::BoundedSequence(t) => write!(f, "{}[<={}]", t.value_type, t.max_size),
+            t => write!(f, "{}", t.clone().inner_type()),
+        }
+    }
+}
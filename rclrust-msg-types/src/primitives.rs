@@ -1,4 +1,5 @@
 use std::fmt;
+use std::mem;
 
 use crate::define_enum_from;
 
@@ -65,6 +66,47 @@ impl BasicType {
         })
     }
 
+    /// Returns the size in bytes.
+    pub const fn size(self) -> usize {
+        match self {
+            Self::I8 | Self::U8 | Self::Bool | Self::Char | Self::Byte => 1,
+            Self::I16 | Self::U16 => 2,
+            Self::I32 | Self::U32 | Self::F32 => 4,
+            Self::I64 | Self::U64 | Self::F64 => 8,
+        }
+    }
+
+    /// Returns the alignment in bytes on the target, which is smaller than the size for 64-bit
+    /// types on e.g. i686.
+    pub const fn align(self) -> usize {
+        match self {
+            Self::I8 | Self::U8 | Self::Bool | Self::Char | Self::Byte => 1,
+            Self::I16 | Self::U16 => mem::align_of::<u16>(),
+            Self::I32 | Self::U32 => mem::align_of::<u32>(),
+            Self::F32 => mem::align_of::<f32>(),
+            Self::I64 | Self::U64 => mem::align_of::<u64>(),
+            Self::F64 => mem::align_of::<f64>(),
+        }
+    }
+
+    pub const fn to_msg_str(self) -> &'static str {
+        match self {
+            Self::I8 => "int8",
+            Self::I16 => "int16",
+            Self::I32 => "int32",
+            Self::I64 => "int64",
+            Self::U8 => "uint8",
+            Self::U16 => "uint16",
+            Self::U32 => "uint32",
+            Self::U64 => "uint64",
+            Self::F32 => "float32",
+            Self::F64 => "float64",
+            Self::Bool => "bool",
+            Self::Char => "char",
+            Self::Byte => "byte",
+        }
+    }
+
     pub const fn to_rust_str(self) -> &'static str {
         match self {
             Self::I8 => "i8",
@@ -82,6 +124,12 @@ impl BasicType {
     }
 }
 
+impl fmt::Display for BasicType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_msg_str())
+    }
+}
+
 /// A type identified by the name
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NamedType(pub String);
@@ -132,6 +180,17 @@ impl GenericString {
     }
 }
 
+impl fmt::Display for GenericString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String => write!(f, "string"),
+            Self::WString => write!(f, "wstring"),
+            Self::BoundedString(max_size) => write!(f, "string<={}", max_size),
+            Self::BoundedWString(max_size) => write!(f, "wstring<={}", max_size),
+        }
+    }
+}
+
 impl From<GenericUnboundedString> for GenericString {
     fn from(t: GenericUnboundedString) -> Self {
         match t {
@@ -148,6 +207,15 @@ pub enum GenericUnboundedString {
     WString,
 }
 
+impl fmt::Display for GenericUnboundedString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String => write!(f, "string"),
+            Self::WString => write!(f, "wstring"),
+        }
+    }
+}
+
 /// A type which can be used inside nested types
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum NestableType {
@@ -162,6 +230,21 @@ define_enum_from!(NestableType, NamedType, Self::NamedType);
 define_enum_from!(NestableType, NamespacedType, Self::NamespacedType);
 define_enum_from!(NestableType, GenericString, Self::GenericString);
 
+impl fmt::Display for NestableType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BasicType(t) => write!(f, "{}", t),
+            Self::NamedType(t) => write!(f, "{}", t),
+            // Messages are referred without the namespace in interface files
+            Self::NamespacedType(t) if t.namespace == "msg" => {
+                write!(f, "{}/{}", t.package, t.name)
+            }
+            Self::NamespacedType(t) => write!(f, "{}", t),
+            Self::GenericString(t) => write!(f, "{}", t),
+        }
+    }
+}
+
 /// A primitive type which can be used for constant
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PrimitiveType {
@@ -175,3 +258,12 @@ define_enum_from!(
     GenericUnboundedString,
     Self::GenericUnboundedString
 );
+
+impl fmt::Display for PrimitiveType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BasicType(t) => write!(f, "{}", t),
+            Self::GenericUnboundedString(t) => write!(f, "{}", t),
+        }
+    }
+}
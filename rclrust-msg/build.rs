@@ -1,25 +1,14 @@
-use std::fs::File;
-use std::io::Write;
+use std::env::VarError;
+use std::fs;
 use std::path::{Path, PathBuf};
 
-use heck::SnakeCase;
-use rclrust_msg_build_helper::codegen;
-use rclrust_msg_build_helper::parse::{get_packages_msgs, RosPackageMsgs};
-use sailfish::TemplateOnce;
-
-#[derive(Debug, TemplateOnce)]
-#[template(path = "mod.rs.stpl", delimiter = '@', escape = false)]
-struct ModuleTemplate<'a> {
-    package: &'a str,
-    msgs: RosPackageMsgs,
-}
+use rclrust_msg_build_helper::parse::get_packages_msgs;
+use rclrust_msg_build_helper::render::{generate, Distro, Options};
 
 fn main() {
     println!("cargo:rerun-if-env-changed=AMENT_PREFIX_PATH");
-    println!("cargo:rerun-if-changed=templates/mod.rs.stpl");
-    println!("cargo:rerun-if-changed=templates/msg.rs.stpl");
-    println!("cargo:rerun-if-changed=templates/srv.rs.stpl");
-    println!("cargo:rerun-if-changed=templates/action.rs.stpl");
+    println!("cargo:rerun-if-env-changed=ROS_DISTRO");
+    println!("cargo:rerun-if-env-changed=RCLRUST_MSG_EXT_DIR");
 
     let ament_prefix_paths =
         std::env::var("AMENT_PREFIX_PATH").expect("$AMENT_PREFIX_PATH is supposed to be set.");
@@ -32,15 +21,31 @@ fn main() {
         .map(Path::new)
         .collect::<Vec<_>>();
 
-    let mut f =
-        File::create(PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("gen.rs")).unwrap();
-
-    for (package, msgs) in get_packages_msgs(&paths).unwrap() {
-        let ctx = ModuleTemplate {
-            package: &package,
-            msgs,
-        };
-
-        write!(f, "{}", ctx.render_once().unwrap()).unwrap();
+    // Hand-written code added to the generated types, see `Options::ext_dir`
+    let ext_dir = std::env::var_os("RCLRUST_MSG_EXT_DIR").map(PathBuf::from);
+    if let Some(ext_dir) = &ext_dir {
+        println!("cargo:rerun-if-changed={}", ext_dir.display());
     }
+
+    let distro = match std::env::var("ROS_DISTRO") {
+        Ok(name) => Distro::parse(&name)
+            .unwrap_or_else(|| panic!("$ROS_DISTRO `{}` is not a supported distro.", name)),
+        Err(VarError::NotPresent) => {
+            println!("cargo:warning=$ROS_DISTRO is not set, so the code is generated for Foxy.");
+            Distro::Foxy
+        }
+        Err(e) => panic!("$ROS_DISTRO is invalid: {}", e),
+    };
+
+    let options = Options {
+        distro,
+        ext_dir,
+        ..Options::default()
+    };
+    let code = generate(&get_packages_msgs(&paths).unwrap(), &options).unwrap();
+    fs::write(
+        PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("gen.rs"),
+        code,
+    )
+    .unwrap();
 }
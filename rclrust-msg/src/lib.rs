@@ -1,7 +1,19 @@
 #![warn(rust_2018_idioms, elided_lifetimes_in_paths)]
 #![allow(clippy::all)]
 
+use std::sync::{Once, RwLock};
+
+pub use rclrust_msg_core::introspection;
+pub use rclrust_msg_core::registry;
 pub use rclrust_msg_core::traits;
 pub use rclrust_msg_core::widestring;
+pub use rclrust_msg_core::yaml;
 
 include!(concat!(env!("OUT_DIR"), "/gen.rs"));
+
+/// Returns the global [`registry::TypeRegistry`] with all the packages of this crate registered.
+pub fn registry() -> &'static RwLock<registry::TypeRegistry> {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| register_all(&mut registry::global().write().unwrap()));
+    registry::global()
+}